@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ff::Field;
+use group::Group;
+use rust_protodanksharding_example::{
+    fixed_base::FixedBaseTable,
+    msm::{g1_lincomb, msm},
+};
+
+fn random_input(num_points: usize) -> (Vec<blstrs::G1Affine>, Vec<blstrs::Scalar>) {
+    let points = (0..num_points)
+        .map(|_| blstrs::G1Projective::random(&mut rand::thread_rng()).into())
+        .collect();
+    let scalars = (0..num_points).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect();
+    (points, scalars)
+}
+
+fn bench_msm(c: &mut Criterion) {
+    let mut group = c.benchmark_group("msm");
+    for num_points in [256, 1024, 4096] {
+        let (points, scalars) = random_input(num_points);
+
+        group.bench_with_input(BenchmarkId::new("blst_multi_exp", num_points), &num_points, |b, _| {
+            b.iter(|| {
+                let points: Vec<_> = points.iter().map(blstrs::G1Projective::from).collect();
+                blstrs::G1Affine::from(blstrs::G1Projective::multi_exp(&points, &scalars))
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("blst_affine", num_points), &num_points, |b, _| {
+            b.iter(|| g1_lincomb(&points, &scalars))
+        });
+        group.bench_with_input(BenchmarkId::new("pippenger_batch_affine", num_points), &num_points, |b, _| {
+            b.iter(|| blstrs::G1Affine::from(msm(&points, &scalars)))
+        });
+        for window_bits in [8, 12] {
+            let table = FixedBaseTable::new(&points, window_bits);
+            let id = BenchmarkId::new(format!("fixed_base_table_w{window_bits}"), num_points);
+            group.bench_with_input(id, &num_points, |b, _| b.iter(|| blstrs::G1Affine::from(table.msm(&scalars))));
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_msm);
+criterion_main!(benches);
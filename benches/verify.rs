@@ -0,0 +1,44 @@
+use blstrs::{Bls12, G2Prepared};
+use criterion::{criterion_group, criterion_main, Criterion};
+use pairing_lib::{group::Group, MillerLoopResult, MultiMillerLoop};
+use rust_protodanksharding_example::{
+    domain::Domain, opening_key::OpeningKey, params::PublicParameters, polynomial::Polynomial, proof::Proof,
+};
+
+// The previous verification: move `z` into G2 and prepare a new G2 element for every proof
+fn verify_z_in_g2(
+    opening_key: &OpeningKey,
+    input_point: blstrs::Scalar,
+    output_point: blstrs::Scalar,
+    poly_comm: blstrs::G1Affine,
+    witness_comm: blstrs::G1Affine,
+) -> bool {
+    let inner_a: blstrs::G1Affine = (poly_comm - (opening_key.g1_gen * output_point)).into();
+    let inner_b: blstrs::G2Affine = (opening_key.tau_g2_gen - (opening_key.g2_gen * input_point)).into();
+    let prepared_inner_b = G2Prepared::from(-inner_b);
+
+    let terms = [(&inner_a, &opening_key.prepared_g2), (&witness_comm, &prepared_inner_b)];
+    Bls12::multi_miller_loop(&terms).final_exponentiation().is_identity().into()
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let domain = Domain::new(256);
+    let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+    let opening_key = &public_parameters.opening_key;
+
+    let poly = Polynomial::new((0..256u64).map(blstrs::Scalar::from).collect());
+    let poly_comm = public_parameters.commit_key.commit(&poly);
+    let input_point = blstrs::Scalar::from(123456u64);
+    let proof = Proof::create(&public_parameters.commit_key, &poly, poly_comm, input_point, &domain);
+    assert!(verify_z_in_g2(opening_key, input_point, proof.output_point, poly_comm, proof.quotient_commitment));
+
+    let mut group = c.benchmark_group("verify");
+    group.bench_function("z_in_g2", |b| {
+        b.iter(|| verify_z_in_g2(opening_key, input_point, proof.output_point, poly_comm, proof.quotient_commitment))
+    });
+    group.bench_function("z_in_g1", |b| b.iter(|| proof.verify(input_point, opening_key)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_verify);
+criterion_main!(benches);
@@ -0,0 +1,96 @@
+// Extracts the mainnet opening key from the trusted setup, and preprocesses the rest of the
+// setup when the `embedded-setup` feature is enabled.
+//
+// The setup file stores compressed points. Decompressing 4096 points needs a square root
+// and a subgroup check per point, so we do that once here and emit uncompressed points.
+//
+// Note: The lagrange points in the file are in the natural order of the roots of unity,
+// which is the order that `Domain` uses. c-kzg applies the bit-reversal permutation
+// after loading, we do not.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={TRUSTED_SETUP_PATH}");
+
+    let contents = std::fs::read_to_string(TRUSTED_SETUP_PATH).expect("could not read trusted setup file");
+    generate_opening_key(&contents);
+
+    #[cfg(feature = "embedded-setup")]
+    embedded_setup::generate(&contents);
+}
+
+const TRUSTED_SETUP_PATH: &str = "trusted_setup.txt";
+
+// Writes the compressed G1 generator, G2 generator and \tau G2, which is all a verifier needs.
+//
+// The points are copied as they are, so this does not need to decode them. `OpeningKey::from_bytes`
+// checks them when they are loaded.
+fn generate_opening_key(contents: &str) {
+    let mut lines = contents.lines().map(str::trim).filter(|line| !line.is_empty());
+    let num_g1_points: usize = lines.next().unwrap().parse().unwrap();
+    let num_g2_points: usize = lines.next().unwrap().parse().unwrap();
+    assert!(num_g2_points >= 2, "the setup needs at least two g2 points");
+
+    let mut lines = lines.skip(num_g1_points);
+    let g2_gen = lines.next().expect("missing g2 point");
+    let tau_g2_gen = lines.next().expect("missing g2 point");
+    let g1_gen = lines.nth(num_g2_points - 2).expect("missing g1 monomial point");
+
+    let bytes: Vec<u8> = [g1_gen, g2_gen, tau_g2_gen].into_iter().flat_map(decode_hex).collect();
+    assert_eq!(bytes.len(), 48 + 2 * 96, "invalid point size in trusted setup");
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    std::fs::write(std::path::Path::new(&out_dir).join("mainnet_opening_key.bin"), bytes).unwrap();
+}
+
+fn decode_hex(hex_str: &str) -> Vec<u8> {
+    let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+    assert!(hex_str.len().is_multiple_of(2), "hex string has an odd length");
+    (0..hex_str.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex_str[i..i + 2], 16).expect("invalid hex character"))
+        .collect()
+}
+
+#[cfg(feature = "embedded-setup")]
+mod embedded_setup {
+    use std::{fs, path::Path};
+
+    use super::decode_hex;
+
+    pub fn generate(contents: &str) {
+        let mut lines = contents.lines().map(str::trim).filter(|line| !line.is_empty());
+
+        let num_g1_points: usize = lines.next().unwrap().parse().unwrap();
+        let num_g2_points: usize = lines.next().unwrap().parse().unwrap();
+        assert!(num_g1_points.is_power_of_two(), "number of g1 points must be a power of two");
+
+        let g1_lagrange: Vec<_> = (0..num_g1_points)
+            .map(|_| decode_g1(lines.next().expect("missing g1 point")))
+            .collect();
+        let g2_monomial: Vec<_> = (0..num_g2_points)
+            .map(|_| decode_g2(lines.next().expect("missing g2 point")))
+            .collect();
+        let g1_monomial: Vec<_> = (0..num_g1_points)
+            .map(|_| decode_g1(lines.next().expect("missing g1 monomial point")))
+            .collect();
+
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let out_dir = Path::new(&out_dir);
+        let g1_bytes: Vec<u8> = g1_lagrange.iter().flat_map(|point| point.to_uncompressed()).collect();
+        let g1_monomial_bytes: Vec<u8> = g1_monomial.iter().flat_map(|point| point.to_uncompressed()).collect();
+        let g2_bytes: Vec<u8> = g2_monomial.iter().flat_map(|point| point.to_uncompressed()).collect();
+        fs::write(out_dir.join("g1_lagrange.bin"), g1_bytes).unwrap();
+        fs::write(out_dir.join("g1_monomial.bin"), g1_monomial_bytes).unwrap();
+        fs::write(out_dir.join("g2_monomial.bin"), g2_bytes).unwrap();
+    }
+
+    fn decode_g1(hex_str: &str) -> blstrs::G1Affine {
+        let bytes: [u8; 48] = decode_hex(hex_str).try_into().expect("g1 point must be 48 bytes");
+        Option::from(blstrs::G1Affine::from_compressed(&bytes)).expect("invalid g1 point in trusted setup")
+    }
+
+    fn decode_g2(hex_str: &str) -> blstrs::G2Affine {
+        let bytes: [u8; 96] = decode_hex(hex_str).try_into().expect("g2 point must be 96 bytes");
+        Option::from(blstrs::G2Affine::from_compressed(&bytes)).expect("invalid g2 point in trusted setup")
+    }
+}
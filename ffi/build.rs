@@ -0,0 +1,185 @@
+//! Generates `ckzg.h` in `OUT_DIR` from the `extern "C"` functions in `src/lib.rs`
+//!
+//! The types are written out by hand below, and every `pub unsafe extern "C" fn` is translated with
+//! its doc comment. The checked in `include/ckzg.h` is compared against the generated header by
+//! the `header_is_up_to_date` test.
+
+use std::{env, fs, path::Path};
+
+const PRELUDE: &str = r#"/* Generated by build.rs from src/lib.rs, do not edit */
+
+#ifndef CKZG_H
+#define CKZG_H
+
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define BYTES_PER_COMMITMENT 48
+#define BYTES_PER_PROOF 48
+#define BYTES_PER_FIELD_ELEMENT 32
+#define FIELD_ELEMENTS_PER_BLOB 4096
+#define BYTES_PER_BLOB (FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT)
+#define FIELD_ELEMENTS_PER_CELL 64
+#define BYTES_PER_CELL (FIELD_ELEMENTS_PER_CELL * BYTES_PER_FIELD_ELEMENT)
+#define CELLS_PER_EXT_BLOB 128
+#define NUM_G2_POINTS 65
+
+typedef enum {
+    C_KZG_OK = 0,
+    C_KZG_BADARGS,
+    C_KZG_ERROR,
+    C_KZG_MALLOC,
+} C_KZG_RET;
+
+typedef struct {
+    uint8_t bytes[32];
+} Bytes32;
+
+typedef struct {
+    uint8_t bytes[48];
+} Bytes48;
+
+typedef struct {
+    uint8_t bytes[BYTES_PER_BLOB];
+} Blob;
+
+typedef struct {
+    uint8_t bytes[BYTES_PER_CELL];
+} Cell;
+
+typedef Bytes48 KZGCommitment;
+typedef Bytes48 KZGProof;
+
+/* An opaque handle to the loaded trusted setup */
+typedef struct {
+    void *inner;
+} KZGSettings;
+"#;
+
+const POSTLUDE: &str = r#"
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CKZG_H */
+"#;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let source = fs::read_to_string("src/lib.rs").expect("failed to read src/lib.rs");
+    let mut header = PRELUDE.to_string();
+    for declaration in declarations(&source) {
+        header.push('\n');
+        header.push_str(&declaration);
+    }
+    header.push_str(POSTLUDE);
+
+    let path = Path::new(&env::var("OUT_DIR").expect("OUT_DIR is set by cargo")).join("ckzg.h");
+    fs::write(path, header).expect("failed to write ckzg.h");
+}
+
+// Translates each exported function into a C declaration, preceded by its doc comment
+fn declarations(source: &str) -> Vec<String> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut declarations = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let Some(rest) = line.strip_prefix("pub unsafe extern \"C\" fn ") else {
+            // Every exported function must be declared the same way, so none of them are skipped
+            assert!(!line.contains("extern \"C\" fn"), "unsupported declaration: {line}");
+            continue;
+        };
+
+        let mut docs = Vec::new();
+        for previous in lines[..index].iter().rev() {
+            let previous = previous.trim();
+            if let Some(doc) = previous.strip_prefix("///") {
+                docs.push(doc.trim().to_string());
+            } else if !previous.starts_with("#[") {
+                break;
+            }
+        }
+        docs.reverse();
+
+        // The signature ends at the opening brace of the body
+        let mut signature = rest.to_string();
+        for next in &lines[index + 1..] {
+            if signature.contains('{') {
+                break;
+            }
+            signature.push_str(next.trim());
+        }
+        let signature = signature[..signature.find('{').unwrap()].trim();
+
+        let name = &signature[..signature.find('(').unwrap()];
+        let parameters_end = signature.rfind(')').unwrap();
+        let parameters = &signature[name.len() + 1..parameters_end];
+        let return_type = signature[parameters_end + 1..].trim().strip_prefix("->").map(str::trim);
+
+        let parameters: Vec<String> = parameters
+            .split(',')
+            .map(str::trim)
+            .filter(|parameter| !parameter.is_empty())
+            .map(|parameter| {
+                let (name, ty) = parameter.split_once(':').unwrap();
+                let (ty, name) = (c_type(ty.trim()), c_name(name.trim()));
+                // Pointers are written as `Blob *blob`
+                if ty.ends_with('*') {
+                    format!("{ty}{name}")
+                } else {
+                    format!("{ty} {name}")
+                }
+            })
+            .collect();
+
+        let mut declaration = String::new();
+        if !docs.is_empty() {
+            declaration.push_str("/*\n");
+            for doc in docs {
+                declaration.push_str(if doc.is_empty() { " *".to_string() } else { format!(" * {doc}") }.as_str());
+                declaration.push('\n');
+            }
+            declaration.push_str(" */\n");
+        }
+        declaration.push_str(&format!(
+            "{} {}({});\n",
+            return_type.map_or("void".to_string(), c_type),
+            name,
+            parameters.join(", ")
+        ));
+        declarations.push(declaration);
+    }
+    declarations
+}
+
+// Parameters that are Rust keywords end in `_`, which is not part of c-kzg's name
+fn c_name(name: &str) -> &str {
+    match name {
+        "in_" => "in",
+        _ => name,
+    }
+}
+
+fn c_type(ty: &str) -> String {
+    if let Some(pointee) = ty.strip_prefix("*mut ") {
+        return format!("{} *", c_type(pointee));
+    }
+    if let Some(pointee) = ty.strip_prefix("*const ") {
+        return format!("const {} *", c_type(pointee));
+    }
+    match ty {
+        "u8" => "uint8_t",
+        "u64" => "uint64_t",
+        "bool" => "bool",
+        "libc::FILE" => "FILE",
+        "C_KZG_RET" | "Bytes32" | "Bytes48" | "Blob" | "Cell" | "KZGCommitment" | "KZGProof" | "KZGSettings" => ty,
+        other => panic!("no C type for `{other}`, add it to the prelude and to c_type"),
+    }
+    .to_string()
+}
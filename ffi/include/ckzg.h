@@ -0,0 +1,124 @@
+/* Generated by build.rs from src/lib.rs, do not edit */
+
+#ifndef CKZG_H
+#define CKZG_H
+
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define BYTES_PER_COMMITMENT 48
+#define BYTES_PER_PROOF 48
+#define BYTES_PER_FIELD_ELEMENT 32
+#define FIELD_ELEMENTS_PER_BLOB 4096
+#define BYTES_PER_BLOB (FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT)
+#define FIELD_ELEMENTS_PER_CELL 64
+#define BYTES_PER_CELL (FIELD_ELEMENTS_PER_CELL * BYTES_PER_FIELD_ELEMENT)
+#define CELLS_PER_EXT_BLOB 128
+#define NUM_G2_POINTS 65
+
+typedef enum {
+    C_KZG_OK = 0,
+    C_KZG_BADARGS,
+    C_KZG_ERROR,
+    C_KZG_MALLOC,
+} C_KZG_RET;
+
+typedef struct {
+    uint8_t bytes[32];
+} Bytes32;
+
+typedef struct {
+    uint8_t bytes[48];
+} Bytes48;
+
+typedef struct {
+    uint8_t bytes[BYTES_PER_BLOB];
+} Blob;
+
+typedef struct {
+    uint8_t bytes[BYTES_PER_CELL];
+} Cell;
+
+typedef Bytes48 KZGCommitment;
+typedef Bytes48 KZGProof;
+
+/* An opaque handle to the loaded trusted setup */
+typedef struct {
+    void *inner;
+} KZGSettings;
+
+/*
+ * Loads the trusted setup from the compressed points
+ *
+ * The lagrange points are in the natural order of the roots of unity, as in the setup file.
+ * `precompute` is the window size of the table for commitments, 0 disables it.
+ */
+C_KZG_RET load_trusted_setup(KZGSettings *out, const uint8_t *g1_monomial_bytes, uint64_t num_g1_monomial_bytes, const uint8_t *g1_lagrange_bytes, uint64_t num_g1_lagrange_bytes, const uint8_t *g2_monomial_bytes, uint64_t num_g2_monomial_bytes, uint64_t precompute);
+
+/*
+ * Loads the trusted setup from a file in the format of `trusted_setup.txt`
+ */
+C_KZG_RET load_trusted_setup_file(KZGSettings *out, FILE *in, uint64_t precompute);
+
+/*
+ * Frees the trusted setup, it is safe to call this more than once
+ */
+void free_trusted_setup(KZGSettings *s);
+
+/*
+ * Computes the commitment to a blob
+ */
+C_KZG_RET blob_to_kzg_commitment(KZGCommitment *out, const Blob *blob, const KZGSettings *s);
+
+/*
+ * Computes the proof of the evaluation of a blob at `z`, and writes the evaluation to `y_out`
+ */
+C_KZG_RET compute_kzg_proof(KZGProof *proof_out, Bytes32 *y_out, const Blob *blob, const Bytes32 *z_bytes, const KZGSettings *s);
+
+/*
+ * Computes the proof of a blob at its Fiat-Shamir challenge, for `verify_blob_kzg_proof`
+ */
+C_KZG_RET compute_blob_kzg_proof(KZGProof *out, const Blob *blob, const Bytes48 *commitment_bytes, const KZGSettings *s);
+
+/*
+ * Checks that the blob committed to by `commitment_bytes` evaluates to `y` at `z`
+ */
+C_KZG_RET verify_kzg_proof(bool *ok, const Bytes48 *commitment_bytes, const Bytes32 *z_bytes, const Bytes32 *y_bytes, const Bytes48 *proof_bytes, const KZGSettings *s);
+
+/*
+ * Checks the proof from `compute_blob_kzg_proof` against the blob and its commitment
+ */
+C_KZG_RET verify_blob_kzg_proof(bool *ok, const Blob *blob, const Bytes48 *commitment_bytes, const Bytes48 *proof_bytes, const KZGSettings *s);
+
+/*
+ * Checks `n` blob proofs at once, which is faster than checking them one by one
+ */
+C_KZG_RET verify_blob_kzg_proof_batch(bool *ok, const Blob *blobs, const Bytes48 *commitments_bytes, const Bytes48 *proofs_bytes, uint64_t n, const KZGSettings *s);
+
+/*
+ * Computes the `CELLS_PER_EXT_BLOB` cells of the extended blob and their proofs
+ *
+ * Either of the outputs may be NULL, if it is not needed.
+ */
+C_KZG_RET compute_cells_and_kzg_proofs(Cell *cells, KZGProof *proofs, const Blob *blob, const KZGSettings *s);
+
+/*
+ * Recovers all of the cells and proofs of an extended blob from at least half of its cells
+ */
+C_KZG_RET recover_cells_and_kzg_proofs(Cell *recovered_cells, KZGProof *recovered_proofs, const uint64_t *cell_indices, const Cell *cells, uint64_t num_cells, const KZGSettings *s);
+
+/*
+ * Verifies the proofs of many cells, which can be cells of different blobs
+ */
+C_KZG_RET verify_cell_kzg_proof_batch(bool *ok, const Bytes48 *commitments_bytes, const uint64_t *cell_indices, const Cell *cells, const Bytes48 *proofs_bytes, uint64_t num_cells, const KZGSettings *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CKZG_H */
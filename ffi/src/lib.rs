@@ -0,0 +1,758 @@
+//! A C interface that mirrors c-kzg-4844, see `include/ckzg.h`
+//!
+//! The functions have the same names, signatures and return codes as in c-kzg, so C and Go code can
+//! link this library instead. `KZGSettings` is an opaque handle here, which only matters to code that
+//! reads its fields.
+#![allow(clippy::missing_safety_doc)]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use group::prime::PrimeCurveAffine;
+use rand_core::RngCore;
+use rust_protodanksharding_example::{
+    cells::{self, CellContext},
+    commit_key::{CommitKey, CommitKeyLagrange},
+    context::KzgContext,
+    domain::Domain,
+    hex::decode_hex,
+    opening_key::OpeningKey,
+    params::PublicParameters,
+    polynomial::Polynomial,
+    proof::Proof,
+    serialization::{deserialize_blob, deserialize_g1_point, deserialize_g1_points_all},
+    BYTES_PER_BLOB, FIELD_ELEMENTS_PER_BLOB, G1_POINT_SERIALIZED_SIZE, G2_POINT_SERIALIZED_SIZE,
+    SCALAR_SERIALIZED_SIZE,
+};
+use sha2::{Digest, Sha256};
+
+/// The number of field elements in a cell
+pub const FIELD_ELEMENTS_PER_CELL: usize = 64;
+/// The number of bytes in a cell
+pub const BYTES_PER_CELL: usize = FIELD_ELEMENTS_PER_CELL * SCALAR_SERIALIZED_SIZE;
+/// The number of cells in an extended blob
+pub const CELLS_PER_EXT_BLOB: usize = 2 * FIELD_ELEMENTS_PER_BLOB / FIELD_ELEMENTS_PER_CELL;
+/// The number of G2 points in the trusted setup
+pub const NUM_G2_POINTS: usize = 65;
+// c-kzg's limit for the precomputation window
+const MAX_PRECOMPUTE: u64 = 15;
+
+/// The return code of every function that can fail
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum C_KZG_RET {
+    C_KZG_OK = 0,
+    C_KZG_BADARGS,
+    C_KZG_ERROR,
+    C_KZG_MALLOC,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bytes32 {
+    pub bytes: [u8; SCALAR_SERIALIZED_SIZE],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bytes48 {
+    pub bytes: [u8; G1_POINT_SERIALIZED_SIZE],
+}
+
+#[repr(C)]
+pub struct Blob {
+    pub bytes: [u8; BYTES_PER_BLOB],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Cell {
+    pub bytes: [u8; BYTES_PER_CELL],
+}
+
+pub type KZGCommitment = Bytes48;
+pub type KZGProof = Bytes48;
+
+/// A handle to the loaded trusted setup, which is freed with `free_trusted_setup`
+#[repr(C)]
+pub struct KZGSettings {
+    inner: *mut Settings,
+}
+
+struct Settings {
+    context: KzgContext,
+    cell_context: CellContext,
+}
+
+/// Loads the trusted setup from the compressed points
+///
+/// The lagrange points are in the natural order of the roots of unity, as in the setup file.
+/// `precompute` is the window size of the table for commitments, 0 disables it.
+#[no_mangle]
+pub unsafe extern "C" fn load_trusted_setup(
+    out: *mut KZGSettings,
+    g1_monomial_bytes: *const u8,
+    num_g1_monomial_bytes: u64,
+    g1_lagrange_bytes: *const u8,
+    num_g1_lagrange_bytes: u64,
+    g2_monomial_bytes: *const u8,
+    num_g2_monomial_bytes: u64,
+    precompute: u64,
+) -> C_KZG_RET {
+    run(|| {
+        let out = mutable(out)?;
+        out.inner = std::ptr::null_mut();
+        let g1_monomial = slice(g1_monomial_bytes, num_g1_monomial_bytes)?;
+        let g1_lagrange = slice(g1_lagrange_bytes, num_g1_lagrange_bytes)?;
+        let g2_monomial = slice(g2_monomial_bytes, num_g2_monomial_bytes)?;
+
+        let settings = new_settings(g1_monomial, g1_lagrange, g2_monomial, precompute)?;
+        out.inner = Box::into_raw(Box::new(settings));
+        Ok(())
+    })
+}
+
+/// Loads the trusted setup from a file in the format of `trusted_setup.txt`
+#[no_mangle]
+pub unsafe extern "C" fn load_trusted_setup_file(out: *mut KZGSettings, in_: *mut libc::FILE, precompute: u64) -> C_KZG_RET {
+    run(|| {
+        let out = mutable(out)?;
+        out.inner = std::ptr::null_mut();
+        if in_.is_null() {
+            return Err(C_KZG_RET::C_KZG_BADARGS);
+        }
+
+        let mut contents = Vec::new();
+        let mut buffer = [0u8; 8192];
+        loop {
+            let read = libc::fread(buffer.as_mut_ptr().cast(), 1, buffer.len(), in_);
+            contents.extend_from_slice(&buffer[..read]);
+            if read < buffer.len() {
+                break;
+            }
+        }
+        let contents = String::from_utf8(contents).map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+        let (g1_monomial, g1_lagrange, g2_monomial) = parse_trusted_setup(&contents)?;
+
+        let settings = new_settings(&g1_monomial, &g1_lagrange, &g2_monomial, precompute)?;
+        out.inner = Box::into_raw(Box::new(settings));
+        Ok(())
+    })
+}
+
+/// Frees the trusted setup, it is safe to call this more than once
+#[no_mangle]
+pub unsafe extern "C" fn free_trusted_setup(s: *mut KZGSettings) {
+    if let Some(s) = s.as_mut() {
+        if !s.inner.is_null() {
+            drop(Box::from_raw(s.inner));
+            s.inner = std::ptr::null_mut();
+        }
+    }
+}
+
+/// Computes the commitment to a blob
+#[no_mangle]
+pub unsafe extern "C" fn blob_to_kzg_commitment(out: *mut KZGCommitment, blob: *const Blob, s: *const KZGSettings) -> C_KZG_RET {
+    run(|| {
+        let settings = settings(s)?;
+        let blob = to_polynomial(reference(blob)?)?;
+        let commitment = settings.context.commit(&blob).map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+        mutable(out)?.bytes = commitment.to_compressed();
+        Ok(())
+    })
+}
+
+/// Computes the proof of the evaluation of a blob at `z`, and writes the evaluation to `y_out`
+#[no_mangle]
+pub unsafe extern "C" fn compute_kzg_proof(
+    proof_out: *mut KZGProof,
+    y_out: *mut Bytes32,
+    blob: *const Blob,
+    z_bytes: *const Bytes32,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    run(|| {
+        let settings = settings(s)?;
+        let blob = to_polynomial(reference(blob)?)?;
+        let input_point = to_scalar(reference(z_bytes)?)?;
+
+        // The commitment is only carried along in the proof, it is not needed for the quotient
+        let proof = settings
+            .context
+            .prove(&blob, blstrs::G1Affine::identity(), input_point)
+            .map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+        mutable(proof_out)?.bytes = proof.quotient_commitment.to_compressed();
+        mutable(y_out)?.bytes = proof.output_point.to_bytes_be();
+        Ok(())
+    })
+}
+
+/// Computes the proof of a blob at its Fiat-Shamir challenge, for `verify_blob_kzg_proof`
+#[no_mangle]
+pub unsafe extern "C" fn compute_blob_kzg_proof(
+    out: *mut KZGProof,
+    blob: *const Blob,
+    commitment_bytes: *const Bytes48,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    run(|| {
+        let settings = settings(s)?;
+        let blob = to_polynomial(reference(blob)?)?;
+        let commitment = to_point(reference(commitment_bytes)?)?;
+        let proof = settings.context.compute_blob_kzg_proof(&blob, commitment).map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+        mutable(out)?.bytes = proof.to_compressed();
+        Ok(())
+    })
+}
+
+/// Checks that the blob committed to by `commitment_bytes` evaluates to `y` at `z`
+#[no_mangle]
+pub unsafe extern "C" fn verify_kzg_proof(
+    ok: *mut bool,
+    commitment_bytes: *const Bytes48,
+    z_bytes: *const Bytes32,
+    y_bytes: *const Bytes32,
+    proof_bytes: *const Bytes48,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    run(|| {
+        let ok = mutable(ok)?;
+        *ok = false;
+        let settings = settings(s)?;
+        let proof = Proof {
+            polynomial_commitment: to_point(reference(commitment_bytes)?)?,
+            quotient_commitment: to_point(reference(proof_bytes)?)?,
+            output_point: to_scalar(reference(y_bytes)?)?,
+        };
+        let input_point = to_scalar(reference(z_bytes)?)?;
+        *ok = settings.context.verify(&proof, input_point);
+        Ok(())
+    })
+}
+
+/// Checks the proof from `compute_blob_kzg_proof` against the blob and its commitment
+#[no_mangle]
+pub unsafe extern "C" fn verify_blob_kzg_proof(
+    ok: *mut bool,
+    blob: *const Blob,
+    commitment_bytes: *const Bytes48,
+    proof_bytes: *const Bytes48,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    run(|| {
+        let ok = mutable(ok)?;
+        *ok = false;
+        let settings = settings(s)?;
+        let blob = to_polynomial(reference(blob)?)?;
+        let commitment = to_point(reference(commitment_bytes)?)?;
+        let proof = to_point(reference(proof_bytes)?)?;
+        *ok = settings.context.verify_blob_kzg_proof(&blob, commitment, proof).map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+        Ok(())
+    })
+}
+
+/// Checks `n` blob proofs at once, which is faster than checking them one by one
+#[no_mangle]
+pub unsafe extern "C" fn verify_blob_kzg_proof_batch(
+    ok: *mut bool,
+    blobs: *const Blob,
+    commitments_bytes: *const Bytes48,
+    proofs_bytes: *const Bytes48,
+    n: u64,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    run(|| {
+        let ok = mutable(ok)?;
+        *ok = false;
+        let settings = settings(s)?;
+        let blobs = slice(blobs, n)?.iter().map(to_polynomial).collect::<Result<Vec<_>, _>>()?;
+        let commitments = to_points(slice(commitments_bytes, n)?)?;
+        let proofs = to_points(slice(proofs_bytes, n)?)?;
+        *ok = settings
+            .context
+            .verify_blob_kzg_proof_batch(&blobs, &commitments, &proofs)
+            .map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+        Ok(())
+    })
+}
+
+/// Computes the `CELLS_PER_EXT_BLOB` cells of the extended blob and their proofs
+///
+/// Either of the outputs may be NULL, if it is not needed.
+#[no_mangle]
+pub unsafe extern "C" fn compute_cells_and_kzg_proofs(
+    cells: *mut Cell,
+    proofs: *mut KZGProof,
+    blob: *const Blob,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    run(|| {
+        if cells.is_null() && proofs.is_null() {
+            return Err(C_KZG_RET::C_KZG_BADARGS);
+        }
+        let settings = settings(s)?;
+        let blob = to_polynomial(reference(blob)?)?;
+
+        if proofs.is_null() {
+            let computed = settings.cell_context.compute_cells(&blob).map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+            write_cells(&computed, slice_mut(cells, CELLS_PER_EXT_BLOB as u64)?);
+            return Ok(());
+        }
+
+        let (computed_cells, computed_proofs) =
+            settings.cell_context.compute_cells_and_kzg_proofs(&blob).map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+        if !cells.is_null() {
+            write_cells(&computed_cells, slice_mut(cells, CELLS_PER_EXT_BLOB as u64)?);
+        }
+        write_points(&computed_proofs, slice_mut(proofs, CELLS_PER_EXT_BLOB as u64)?);
+        Ok(())
+    })
+}
+
+/// Recovers all of the cells and proofs of an extended blob from at least half of its cells
+#[no_mangle]
+pub unsafe extern "C" fn recover_cells_and_kzg_proofs(
+    recovered_cells: *mut Cell,
+    recovered_proofs: *mut KZGProof,
+    cell_indices: *const u64,
+    cells: *const Cell,
+    num_cells: u64,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    run(|| {
+        let settings = settings(s)?;
+        let cell_indices = to_indices(slice(cell_indices, num_cells)?);
+        let cells = slice(cells, num_cells)?.iter().map(to_cell).collect::<Result<Vec<_>, _>>()?;
+
+        let (computed_cells, computed_proofs) = settings
+            .cell_context
+            .recover_cells_and_kzg_proofs(&cell_indices, &cells)
+            .map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+        write_cells(&computed_cells, slice_mut(recovered_cells, CELLS_PER_EXT_BLOB as u64)?);
+        write_points(&computed_proofs, slice_mut(recovered_proofs, CELLS_PER_EXT_BLOB as u64)?);
+        Ok(())
+    })
+}
+
+/// Verifies the proofs of many cells, which can be cells of different blobs
+#[no_mangle]
+pub unsafe extern "C" fn verify_cell_kzg_proof_batch(
+    ok: *mut bool,
+    commitments_bytes: *const Bytes48,
+    cell_indices: *const u64,
+    cells: *const Cell,
+    proofs_bytes: *const Bytes48,
+    num_cells: u64,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    run(|| {
+        let ok = mutable(ok)?;
+        *ok = false;
+        let settings = settings(s)?;
+        let commitments_bytes = slice(commitments_bytes, num_cells)?;
+        let raw_cell_indices = slice(cell_indices, num_cells)?;
+        let raw_cells = slice(cells, num_cells)?;
+        let proofs_bytes = slice(proofs_bytes, num_cells)?;
+
+        let commitments = to_points(commitments_bytes)?;
+        let proofs = to_points(proofs_bytes)?;
+        let cells = raw_cells.iter().map(to_cell).collect::<Result<Vec<_>, _>>()?;
+
+        // The randomness is derived from all of the inputs, so the result does not depend on an rng
+        let mut transcript = Sha256::new();
+        transcript.update((num_cells).to_be_bytes());
+        for (((commitment, cell_index), cell), proof) in
+            commitments_bytes.iter().zip(raw_cell_indices).zip(raw_cells).zip(proofs_bytes)
+        {
+            transcript.update(commitment.bytes);
+            transcript.update(cell_index.to_be_bytes());
+            transcript.update(cell.bytes);
+            transcript.update(proof.bytes);
+        }
+        let rng = FiatShamirRng::new(transcript.finalize().into());
+
+        *ok = settings
+            .cell_context
+            .verify_cell_kzg_proof_batch(&commitments, &to_indices(raw_cell_indices), &cells, &proofs, rng)
+            .map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+        Ok(())
+    })
+}
+
+// Runs `f`, and turns a panic into `C_KZG_ERROR` instead of unwinding into C
+fn run(f: impl FnOnce() -> Result<(), C_KZG_RET>) -> C_KZG_RET {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => C_KZG_RET::C_KZG_OK,
+        Ok(Err(ret)) => ret,
+        Err(_) => C_KZG_RET::C_KZG_ERROR,
+    }
+}
+
+fn new_settings(
+    g1_monomial_bytes: &[u8],
+    g1_lagrange_bytes: &[u8],
+    g2_monomial_bytes: &[u8],
+    precompute: u64,
+) -> Result<Settings, C_KZG_RET> {
+    if g1_monomial_bytes.len() != FIELD_ELEMENTS_PER_BLOB * G1_POINT_SERIALIZED_SIZE
+        || g1_lagrange_bytes.len() != FIELD_ELEMENTS_PER_BLOB * G1_POINT_SERIALIZED_SIZE
+        || g2_monomial_bytes.len() != NUM_G2_POINTS * G2_POINT_SERIALIZED_SIZE
+        || precompute > MAX_PRECOMPUTE
+    {
+        return Err(C_KZG_RET::C_KZG_BADARGS);
+    }
+
+    let decode_g1 = |bytes: &[u8]| {
+        let chunks: Vec<[u8; G1_POINT_SERIALIZED_SIZE]> =
+            bytes.chunks_exact(G1_POINT_SERIALIZED_SIZE).map(|chunk| chunk.try_into().unwrap()).collect();
+        deserialize_g1_points_all(&chunks).map_err(|_| C_KZG_RET::C_KZG_BADARGS)
+    };
+    let g1_monomial = decode_g1(g1_monomial_bytes)?;
+    let g1_lagrange = decode_g1(g1_lagrange_bytes)?;
+    let g2_monomial = g2_monomial_bytes
+        .chunks_exact(G2_POINT_SERIALIZED_SIZE)
+        .map(|chunk| Option::from(blstrs::G2Affine::from_compressed(chunk.try_into().unwrap())).ok_or(C_KZG_RET::C_KZG_BADARGS))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let opening_key = OpeningKey::new(blstrs::G1Affine::generator(), g2_monomial[0], g2_monomial[1]);
+    let mut commit_key = CommitKeyLagrange::new(g1_lagrange);
+    let public_parameters =
+        PublicParameters::new(CommitKey::new(g1_monomial), commit_key.clone(), opening_key.clone()).with_g2_monomial(g2_monomial);
+
+    let mut transcript = Sha256::new();
+    transcript.update(g1_monomial_bytes);
+    transcript.update(g1_lagrange_bytes);
+    transcript.update(g2_monomial_bytes);
+    if !public_parameters.verify_consistency(FiatShamirRng::new(transcript.finalize().into())) {
+        return Err(C_KZG_RET::C_KZG_BADARGS);
+    }
+
+    // A window of one bit would not save anything
+    if precompute >= 2 {
+        commit_key.precompute(precompute as usize);
+    }
+    let context = KzgContext::new(Domain::new(FIELD_ELEMENTS_PER_BLOB), commit_key, opening_key)
+        .map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+    let cell_context = CellContext::new(&public_parameters, FIELD_ELEMENTS_PER_BLOB, FIELD_ELEMENTS_PER_CELL)
+        .map_err(|_| C_KZG_RET::C_KZG_BADARGS)?;
+    Ok(Settings { context, cell_context })
+}
+
+// Splits the setup file into the bytes of the g1 monomial, g1 lagrange and g2 monomial points
+#[allow(clippy::type_complexity)]
+fn parse_trusted_setup(contents: &str) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), C_KZG_RET> {
+    let mut lines = contents.lines().map(str::trim).filter(|line| !line.is_empty());
+    let mut next_number = || -> Result<usize, C_KZG_RET> {
+        lines.next().and_then(|line| line.parse().ok()).ok_or(C_KZG_RET::C_KZG_BADARGS)
+    };
+    let num_g1_points = next_number()?;
+    let num_g2_points = next_number()?;
+
+    let mut read_points = |num_points: usize, point_size: usize| -> Result<Vec<u8>, C_KZG_RET> {
+        let mut bytes = Vec::with_capacity(num_points * point_size);
+        for _ in 0..num_points {
+            let line = lines.next().ok_or(C_KZG_RET::C_KZG_BADARGS)?;
+            bytes.extend(decode_hex(&format!("0x{line}"), point_size).map_err(|_| C_KZG_RET::C_KZG_BADARGS)?);
+        }
+        Ok(bytes)
+    };
+    let g1_lagrange = read_points(num_g1_points, G1_POINT_SERIALIZED_SIZE)?;
+    let g2_monomial = read_points(num_g2_points, G2_POINT_SERIALIZED_SIZE)?;
+    let g1_monomial = read_points(num_g1_points, G1_POINT_SERIALIZED_SIZE)?;
+    Ok((g1_monomial, g1_lagrange, g2_monomial))
+}
+
+unsafe fn settings<'a>(s: *const KZGSettings) -> Result<&'a Settings, C_KZG_RET> {
+    reference(s)?.inner.as_ref().ok_or(C_KZG_RET::C_KZG_BADARGS)
+}
+
+unsafe fn reference<'a, T>(pointer: *const T) -> Result<&'a T, C_KZG_RET> {
+    pointer.as_ref().ok_or(C_KZG_RET::C_KZG_BADARGS)
+}
+
+unsafe fn mutable<'a, T>(pointer: *mut T) -> Result<&'a mut T, C_KZG_RET> {
+    pointer.as_mut().ok_or(C_KZG_RET::C_KZG_BADARGS)
+}
+
+// An empty array may be passed as NULL
+unsafe fn slice<'a, T>(pointer: *const T, len: u64) -> Result<&'a [T], C_KZG_RET> {
+    match (pointer.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(C_KZG_RET::C_KZG_BADARGS),
+        (false, len) => Ok(std::slice::from_raw_parts(pointer, len as usize)),
+    }
+}
+
+unsafe fn slice_mut<'a, T>(pointer: *mut T, len: u64) -> Result<&'a mut [T], C_KZG_RET> {
+    if pointer.is_null() {
+        return Err(C_KZG_RET::C_KZG_BADARGS);
+    }
+    Ok(std::slice::from_raw_parts_mut(pointer, len as usize))
+}
+
+fn to_polynomial(blob: &Blob) -> Result<Polynomial, C_KZG_RET> {
+    deserialize_blob(&blob.bytes).map_err(|_| C_KZG_RET::C_KZG_BADARGS)
+}
+
+fn to_point(bytes: &Bytes48) -> Result<blstrs::G1Affine, C_KZG_RET> {
+    deserialize_g1_point(&bytes.bytes).map_err(|_| C_KZG_RET::C_KZG_BADARGS)
+}
+
+fn to_points(bytes: &[Bytes48]) -> Result<Vec<blstrs::G1Affine>, C_KZG_RET> {
+    let bytes: Vec<_> = bytes.iter().map(|bytes| bytes.bytes).collect();
+    deserialize_g1_points_all(&bytes).map_err(|_| C_KZG_RET::C_KZG_BADARGS)
+}
+
+fn to_scalar(bytes: &Bytes32) -> Result<blstrs::Scalar, C_KZG_RET> {
+    Option::from(blstrs::Scalar::from_bytes_be(&bytes.bytes)).ok_or(C_KZG_RET::C_KZG_BADARGS)
+}
+
+fn to_cell(cell: &Cell) -> Result<cells::Cell, C_KZG_RET> {
+    cell.bytes
+        .chunks_exact(SCALAR_SERIALIZED_SIZE)
+        .map(|chunk| Option::from(blstrs::Scalar::from_bytes_be(chunk.try_into().unwrap())).ok_or(C_KZG_RET::C_KZG_BADARGS))
+        .collect()
+}
+
+// Indices that do not fit in a usize are out of range anyway
+fn to_indices(indices: &[u64]) -> Vec<usize> {
+    indices.iter().map(|index| usize::try_from(*index).unwrap_or(usize::MAX)).collect()
+}
+
+fn write_cells(cells: &[cells::Cell], out: &mut [Cell]) {
+    for (cell, out) in cells.iter().zip(out) {
+        for (evaluation, chunk) in cell.iter().zip(out.bytes.chunks_exact_mut(SCALAR_SERIALIZED_SIZE)) {
+            chunk.copy_from_slice(&evaluation.to_bytes_be());
+        }
+    }
+}
+
+fn write_points(points: &[blstrs::G1Affine], out: &mut [Bytes48]) {
+    for (point, out) in points.iter().zip(out) {
+        out.bytes = point.to_compressed();
+    }
+}
+
+// An rng that expands a hash of the inputs, for the batch checks that take an rng
+struct FiatShamirRng {
+    seed: [u8; 32],
+    counter: u64,
+    block: [u8; 32],
+    position: usize,
+}
+
+impl FiatShamirRng {
+    fn new(seed: [u8; 32]) -> Self {
+        FiatShamirRng { seed, counter: 0, block: [0; 32], position: 32 }
+    }
+}
+
+impl RngCore for FiatShamirRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            if self.position == self.block.len() {
+                let mut hasher = Sha256::new();
+                hasher.update(self.seed);
+                hasher.update(self.counter.to_be_bytes());
+                self.block = hasher.finalize().into();
+                self.counter += 1;
+                self.position = 0;
+            }
+            *byte = self.block[self.position];
+            self.position += 1;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn header_is_up_to_date() {
+        // After changing an exported function, copy the generated header from
+        // `target/*/build/protodanksharding-ffi-*/out/ckzg.h` to `include/ckzg.h`
+        assert!(
+            include_str!(concat!(env!("OUT_DIR"), "/ckzg.h")) == include_str!("../include/ckzg.h"),
+            "include/ckzg.h does not match the header that build.rs generates from src/lib.rs"
+        );
+    }
+
+    fn load_mainnet_setup() -> KZGSettings {
+        let path = CString::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../trusted_setup.txt")).unwrap();
+        let mut settings = KZGSettings { inner: std::ptr::null_mut() };
+        unsafe {
+            let file = libc::fopen(path.as_ptr(), c"r".as_ptr());
+            assert!(!file.is_null());
+            assert_eq!(load_trusted_setup_file(&mut settings, file, 0), C_KZG_RET::C_KZG_OK);
+            libc::fclose(file);
+        }
+        settings
+    }
+
+    fn test_blob(seed: u64) -> Box<Blob> {
+        let mut blob = Box::new(Blob { bytes: [0; BYTES_PER_BLOB] });
+        for (index, chunk) in blob.bytes.chunks_exact_mut(SCALAR_SERIALIZED_SIZE).enumerate() {
+            chunk.copy_from_slice(&blstrs::Scalar::from(seed * 1000 + index as u64).to_bytes_be());
+        }
+        blob
+    }
+
+    // A blob of hashes, with the top byte cleared so that every field element is canonical
+    fn hashed_blob(seed: u8) -> Box<Blob> {
+        let mut blob = Box::new(Blob { bytes: [0; BYTES_PER_BLOB] });
+        for (index, chunk) in blob.bytes.chunks_exact_mut(SCALAR_SERIALIZED_SIZE).enumerate() {
+            let mut hasher = Sha256::new();
+            hasher.update([seed]);
+            hasher.update((index as u64).to_be_bytes());
+            chunk.copy_from_slice(&hasher.finalize());
+            chunk[0] = 0;
+        }
+        blob
+    }
+
+    fn from_hex<const N: usize>(string: &str) -> [u8; N] {
+        rust_protodanksharding_example::hex::decode_hex_array(string).unwrap()
+    }
+
+    // The expected values were computed with c-kzg-4844 v2.1.8 and its embedded mainnet setup
+    #[test]
+    fn mainnet_round_trip() {
+        let mut s = load_mainnet_setup();
+        let blobs = [*hashed_blob(1), *hashed_blob(2)];
+        let expected_commitments = [
+            from_hex("0xb56912e686c9764439b9105c3cf2301ba473b578d312a1b2d3c95d247f6a61f7fadc1564764345b030f85a20fd732d6d"),
+            from_hex("0xac3d5a652ed4fe255cd644c47c11c0d3a34439ccf3dfbf128375601bf1adcd090570cf36aa13bf31c30394899af4fab2"),
+        ];
+        let expected_proofs = [
+            from_hex("0x94f0861c25f97c6d448bafccf99cb911ef126978ee8eee69669d8728fa93164b635ef42e79afbababd9031b74b4ca36e"),
+            from_hex("0x87574441f920e27108698910b1d938ea51503b95e35922c524f99004be936958562611e8b526e30d3dfe7d0e2e79121e"),
+        ];
+        let mut ok = false;
+
+        unsafe {
+            let mut commitments = [Bytes48 { bytes: [0; 48] }; 2];
+            let mut proofs = [Bytes48 { bytes: [0; 48] }; 2];
+            for index in 0..2 {
+                assert_eq!(blob_to_kzg_commitment(&mut commitments[index], &blobs[index], &s), C_KZG_RET::C_KZG_OK);
+                assert_eq!(commitments[index].bytes, expected_commitments[index]);
+                assert_eq!(
+                    compute_blob_kzg_proof(&mut proofs[index], &blobs[index], &commitments[index], &s),
+                    C_KZG_RET::C_KZG_OK
+                );
+                assert_eq!(proofs[index].bytes, expected_proofs[index]);
+                assert_eq!(verify_blob_kzg_proof(&mut ok, &blobs[index], &commitments[index], &proofs[index], &s), C_KZG_RET::C_KZG_OK);
+                assert!(ok);
+            }
+            let ret = verify_blob_kzg_proof_batch(&mut ok, blobs.as_ptr(), commitments.as_ptr(), proofs.as_ptr(), 2, &s);
+            assert_eq!(ret, C_KZG_RET::C_KZG_OK);
+            assert!(ok);
+            proofs.swap(0, 1);
+            let ret = verify_blob_kzg_proof_batch(&mut ok, blobs.as_ptr(), commitments.as_ptr(), proofs.as_ptr(), 2, &s);
+            assert_eq!(ret, C_KZG_RET::C_KZG_OK);
+            assert!(!ok);
+
+            let z = Bytes32 { bytes: from_hex("0x004e519ae499312b29433b7dd8a97ff068defcba9755b6d5d00e84c524d67b06") };
+            let (mut proof, mut y) = (Bytes48 { bytes: [0; 48] }, Bytes32 { bytes: [0; 32] });
+            assert_eq!(compute_kzg_proof(&mut proof, &mut y, &blobs[0], &z, &s), C_KZG_RET::C_KZG_OK);
+            assert_eq!(y.bytes, from_hex("0x40323ffb26d21680c65f7440fde9ffbdbe070e099bee6f9d0c799b5927fdfd13"));
+            assert_eq!(
+                proof.bytes,
+                from_hex("0x8e2d79e1478feda965cd00f9a1fd176bbc2e2a4a6c5bde793cc2891ad140faaf44b0c2d4a14cd62a42288d886955c30a")
+            );
+            assert_eq!(verify_kzg_proof(&mut ok, &commitments[0], &z, &y, &proof, &s), C_KZG_RET::C_KZG_OK);
+            assert!(ok);
+            assert_eq!(verify_kzg_proof(&mut ok, &commitments[1], &z, &y, &proof, &s), C_KZG_RET::C_KZG_OK);
+            assert!(!ok);
+
+            let mut cells = vec![Cell { bytes: [0; BYTES_PER_CELL] }; CELLS_PER_EXT_BLOB];
+            let mut cell_proofs = vec![Bytes48 { bytes: [0; 48] }; CELLS_PER_EXT_BLOB];
+            let ret = compute_cells_and_kzg_proofs(cells.as_mut_ptr(), cell_proofs.as_mut_ptr(), &blobs[0], &s);
+            assert_eq!(ret, C_KZG_RET::C_KZG_OK);
+            // The cells and proofs are compared through the hash of their concatenation, and one of each in full
+            let cells_hash: [u8; 32] =
+                cells.iter().fold(Sha256::new(), |hasher, cell| hasher.chain_update(cell.bytes)).finalize().into();
+            let proofs_hash: [u8; 32] =
+                cell_proofs.iter().fold(Sha256::new(), |hasher, proof| hasher.chain_update(proof.bytes)).finalize().into();
+            assert_eq!(cells_hash, from_hex("0xc12ba245346322564ca3beb54449b476d9d673dd9b598107c48f30e388fc4764"));
+            assert_eq!(proofs_hash, from_hex("0xfa6ef9e6f8613ef557264e7b73665b0087325ddefecc4f961e6f50c511d3edd2"));
+            assert_eq!(
+                cells[5].bytes[..SCALAR_SERIALIZED_SIZE],
+                from_hex::<32>("0x0084813c9f24977649edc7af9952050947c0ef66bdff13f7962e4dae89a09836")
+            );
+            assert_eq!(
+                cell_proofs[5].bytes,
+                from_hex("0x8d542c81c746eb573f179c39c8e1538384e7747dcc6690dc29ee465e6802fe1a21d941432ff179a3cae97e2b4ae28b36")
+            );
+
+            let indices: Vec<u64> = (0..CELLS_PER_EXT_BLOB as u64).step_by(2).collect();
+            let half_cells: Vec<Cell> = indices.iter().map(|index| cells[*index as usize]).collect();
+            let half_proofs: Vec<Bytes48> = indices.iter().map(|index| cell_proofs[*index as usize]).collect();
+            let half_commitments = vec![commitments[0]; indices.len()];
+            let ret = verify_cell_kzg_proof_batch(
+                &mut ok,
+                half_commitments.as_ptr(),
+                indices.as_ptr(),
+                half_cells.as_ptr(),
+                half_proofs.as_ptr(),
+                indices.len() as u64,
+                &s,
+            );
+            assert_eq!(ret, C_KZG_RET::C_KZG_OK);
+            assert!(ok);
+
+            let mut recovered_cells = vec![Cell { bytes: [0; BYTES_PER_CELL] }; CELLS_PER_EXT_BLOB];
+            let mut recovered_proofs = vec![Bytes48 { bytes: [0; 48] }; CELLS_PER_EXT_BLOB];
+            let ret = recover_cells_and_kzg_proofs(
+                recovered_cells.as_mut_ptr(),
+                recovered_proofs.as_mut_ptr(),
+                indices.as_ptr(),
+                half_cells.as_ptr(),
+                indices.len() as u64,
+                &s,
+            );
+            assert_eq!(ret, C_KZG_RET::C_KZG_OK);
+            assert!(recovered_cells.iter().zip(&cells).all(|(recovered, cell)| recovered.bytes == cell.bytes));
+            assert_eq!(recovered_proofs, cell_proofs);
+
+            free_trusted_setup(&mut s);
+            free_trusted_setup(&mut s);
+        }
+    }
+
+    #[test]
+    fn bad_arguments() {
+        let mut commitment = Bytes48 { bytes: [0; 48] };
+        let blob = test_blob(1);
+        let mut settings = KZGSettings { inner: std::ptr::null_mut() };
+        unsafe {
+            assert_eq!(blob_to_kzg_commitment(&mut commitment, &*blob, &settings), C_KZG_RET::C_KZG_BADARGS);
+            assert_eq!(blob_to_kzg_commitment(&mut commitment, &*blob, std::ptr::null()), C_KZG_RET::C_KZG_BADARGS);
+
+            let points = [0u8; G1_POINT_SERIALIZED_SIZE];
+            let ret = load_trusted_setup(&mut settings, points.as_ptr(), 48, points.as_ptr(), 48, points.as_ptr(), 48, 0);
+            assert_eq!(ret, C_KZG_RET::C_KZG_BADARGS);
+            assert!(settings.inner.is_null());
+        }
+
+        assert_eq!(parse_trusted_setup("4096\n65\nzz\n"), Err(C_KZG_RET::C_KZG_BADARGS));
+        let mut non_canonical = test_blob(1);
+        non_canonical.bytes[..SCALAR_SERIALIZED_SIZE].fill(0xff);
+        assert_eq!(to_polynomial(&non_canonical).unwrap_err(), C_KZG_RET::C_KZG_BADARGS);
+    }
+}
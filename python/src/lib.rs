@@ -0,0 +1,433 @@
+//! Python bindings for prototyping, built with `maturin develop` from this directory
+//!
+//! The classes wrap the same types as the Rust API. Field elements are passed as 32 byte big endian
+//! `bytes` and points as compressed `bytes`. Invalid arguments raise `ValueError`, and bytes that do
+//! not decode raise its subclass `DecodingError`.
+
+use std::sync::Arc;
+
+use ff::PrimeField;
+use pyo3::{create_exception, exceptions::PyValueError, prelude::*, types::PyBytes};
+use rust_protodanksharding_example::{
+    commit_key::CommitKeyLagrange,
+    domain::Domain,
+    opening_key::OpeningKey,
+    polynomial::Polynomial,
+    proof::Proof,
+    serialization::{deserialize_blob, deserialize_g1_point, deserialize_g1_points_all, serialize_blob, BlobDecodingError},
+    G1_POINT_SERIALIZED_SIZE, G2_POINT_SERIALIZED_SIZE, SCALAR_SERIALIZED_SIZE,
+};
+
+create_exception!(protodanksharding, DecodingError, PyValueError, "Bytes that are not a valid field element or point");
+
+/// The size of `Proof.to_bytes`, the two commitments followed by the output point
+const PROOF_SERIALIZED_SIZE: usize = 2 * G1_POINT_SERIALIZED_SIZE + SCALAR_SERIALIZED_SIZE;
+
+/// The evaluations of a polynomial over a domain of roots of unity
+///
+/// The size is padded to the next power of two, like `Domain::new` in Rust.
+#[pyclass(name = "Domain", module = "protodanksharding", frozen)]
+struct PyDomain {
+    inner: Arc<Domain>,
+}
+
+#[pymethods]
+impl PyDomain {
+    #[new]
+    fn new(size: usize) -> PyResult<Self> {
+        if size == 0 || size > 1 << blstrs::Scalar::S {
+            return Err(PyValueError::new_err(format!(
+                "domain size must be between 1 and 2^{}, got {size}",
+                blstrs::Scalar::S
+            )));
+        }
+        Ok(PyDomain { inner: Domain::get(size) })
+    }
+
+    #[getter]
+    fn size(&self) -> usize {
+        self.inner.size()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.size()
+    }
+
+    /// The roots of unity in their natural order
+    fn roots<'py>(&self, py: Python<'py>) -> Vec<Bound<'py, PyBytes>> {
+        scalars_to_bytes(py, self.inner.roots())
+    }
+
+    /// Evaluates the polynomial with these coefficients over the domain
+    fn fft<'py>(&self, py: Python<'py>, coefficients: Vec<Vec<u8>>) -> PyResult<Vec<Bound<'py, PyBytes>>> {
+        let coefficients = self.scalars_of_domain_size(&coefficients)?;
+        Ok(scalars_to_bytes(py, &self.inner.fft_scalars(coefficients)))
+    }
+
+    /// Interpolates the evaluations over the domain, returning the coefficients
+    fn ifft<'py>(&self, py: Python<'py>, evaluations: Vec<Vec<u8>>) -> PyResult<Vec<Bound<'py, PyBytes>>> {
+        let evaluations = self.scalars_of_domain_size(&evaluations)?;
+        Ok(scalars_to_bytes(py, &self.inner.ifft_scalars(evaluations)))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Domain({})", self.inner.size())
+    }
+}
+
+impl PyDomain {
+    fn scalars_of_domain_size(&self, elements: &[Vec<u8>]) -> PyResult<Vec<blstrs::Scalar>> {
+        check_size("elements", elements.len(), "domain size", self.inner.size())?;
+        scalars_from_bytes(elements)
+    }
+}
+
+/// A polynomial in lagrange form, by its evaluations over the roots of unity
+#[pyclass(name = "Polynomial", module = "protodanksharding", frozen, eq)]
+#[derive(PartialEq)]
+struct PyPolynomial {
+    inner: Polynomial,
+}
+
+#[pymethods]
+impl PyPolynomial {
+    #[new]
+    fn new(evaluations: Vec<Vec<u8>>) -> PyResult<Self> {
+        if !evaluations.len().is_power_of_two() {
+            return Err(PyValueError::new_err(format!(
+                "number of evaluations must be a power of two, got {}",
+                evaluations.len()
+            )));
+        }
+        Ok(PyPolynomial { inner: Polynomial::new(scalars_from_bytes(&evaluations)?) })
+    }
+
+    /// Deserializes a blob, whose field elements are in bit reversed order
+    #[staticmethod]
+    fn from_blob(blob: &[u8]) -> PyResult<Self> {
+        let inner = deserialize_blob(blob).map_err(|error| match error {
+            BlobDecodingError::InvalidLength { .. } => PyValueError::new_err(error.to_string()),
+            BlobDecodingError::NonCanonicalFieldElement { .. } => DecodingError::new_err(error.to_string()),
+        })?;
+        Ok(PyPolynomial { inner })
+    }
+
+    fn to_blob<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &serialize_blob(&self.inner))
+    }
+
+    #[getter]
+    fn evaluations<'py>(&self, py: Python<'py>) -> Vec<Bound<'py, PyBytes>> {
+        scalars_to_bytes(py, &self.inner.evaluations)
+    }
+
+    /// Evaluates the polynomial at any point, inside or outside of the domain
+    fn evaluate<'py>(&self, py: Python<'py>, z: &[u8], domain: &PyDomain) -> PyResult<Bound<'py, PyBytes>> {
+        check_size("evaluations", self.inner.evaluations.len(), "domain size", domain.inner.size())?;
+        let z = scalar_from_bytes(z)?;
+        Ok(PyBytes::new(py, &self.inner.evaluate(z, &domain.inner).to_bytes_be()))
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.evaluations.len()
+    }
+}
+
+/// The trusted setup in lagrange form, for committing to polynomials
+#[pyclass(name = "CommitKeyLagrange", module = "protodanksharding")]
+struct PyCommitKeyLagrange {
+    inner: CommitKeyLagrange,
+}
+
+#[pymethods]
+impl PyCommitKeyLagrange {
+    /// Takes the compressed points concatenated, in the natural order of the roots of unity
+    #[new]
+    fn new(points: &[u8]) -> PyResult<Self> {
+        if !points.len().is_multiple_of(G1_POINT_SERIALIZED_SIZE) || points.len() < 2 * G1_POINT_SERIALIZED_SIZE {
+            return Err(PyValueError::new_err(format!(
+                "expected at least two points of {G1_POINT_SERIALIZED_SIZE} bytes, but received {} bytes",
+                points.len()
+            )));
+        }
+        let points: Vec<[u8; G1_POINT_SERIALIZED_SIZE]> =
+            points.chunks_exact(G1_POINT_SERIALIZED_SIZE).map(|chunk| chunk.try_into().unwrap()).collect();
+        let points = deserialize_g1_points_all(&points).map_err(|error| DecodingError::new_err(error.to_string()))?;
+        Ok(PyCommitKeyLagrange { inner: CommitKeyLagrange::new(points) })
+    }
+
+    /// Precomputes a table with windows of `window_bits` bits, see `CommitKeyLagrange::precompute`
+    fn precompute(&mut self, py: Python<'_>, window_bits: usize) -> PyResult<()> {
+        if !(2..=24).contains(&window_bits) {
+            return Err(PyValueError::new_err(format!("window size must be between 2 and 24 bits, got {window_bits}")));
+        }
+        py.allow_threads(|| self.inner.precompute(window_bits));
+        Ok(())
+    }
+
+    fn commit<'py>(&self, py: Python<'py>, polynomial: &PyPolynomial) -> PyResult<Bound<'py, PyBytes>> {
+        check_size("evaluations", polynomial.inner.evaluations.len(), "points", self.inner.points().len())?;
+        let commitment = py.allow_threads(|| self.inner.commit(&polynomial.inner));
+        Ok(PyBytes::new(py, &commitment.to_compressed()))
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.points().len()
+    }
+}
+
+/// A proof that the committed polynomial evaluates to `output_point` at some input point
+#[pyclass(name = "Proof", module = "protodanksharding", frozen)]
+struct PyProof {
+    inner: Proof,
+}
+
+#[pymethods]
+impl PyProof {
+    #[new]
+    fn new(polynomial_commitment: &[u8], quotient_commitment: &[u8], output_point: &[u8]) -> PyResult<Self> {
+        Ok(PyProof {
+            inner: Proof {
+                polynomial_commitment: g1_from_bytes(polynomial_commitment)?,
+                quotient_commitment: g1_from_bytes(quotient_commitment)?,
+                output_point: scalar_from_bytes(output_point)?,
+            },
+        })
+    }
+
+    /// Creates the proof of `polynomial` at `input_point`, see `Proof::create`
+    #[staticmethod]
+    fn create(
+        py: Python<'_>,
+        commit_key: &PyCommitKeyLagrange,
+        polynomial: &PyPolynomial,
+        polynomial_commitment: &[u8],
+        input_point: &[u8],
+        domain: &PyDomain,
+    ) -> PyResult<Self> {
+        check_size("evaluations", polynomial.inner.evaluations.len(), "domain size", domain.inner.size())?;
+        check_size("evaluations", polynomial.inner.evaluations.len(), "points", commit_key.inner.points().len())?;
+        let polynomial_commitment = g1_from_bytes(polynomial_commitment)?;
+        let input_point = scalar_from_bytes(input_point)?;
+
+        let inner = py.allow_threads(|| {
+            Proof::create(&commit_key.inner, &polynomial.inner, polynomial_commitment, input_point, &domain.inner)
+        });
+        Ok(PyProof { inner })
+    }
+
+    /// Deserializes the output of `to_bytes`
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        check_size("bytes", bytes.len(), "proof size", PROOF_SERIALIZED_SIZE)?;
+        let (polynomial_commitment, rest) = bytes.split_at(G1_POINT_SERIALIZED_SIZE);
+        let (quotient_commitment, output_point) = rest.split_at(G1_POINT_SERIALIZED_SIZE);
+        PyProof::new(polynomial_commitment, quotient_commitment, output_point)
+    }
+
+    /// The polynomial commitment, the quotient commitment and the output point concatenated
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        let mut bytes = Vec::with_capacity(PROOF_SERIALIZED_SIZE);
+        bytes.extend_from_slice(&self.inner.polynomial_commitment.to_compressed());
+        bytes.extend_from_slice(&self.inner.quotient_commitment.to_compressed());
+        bytes.extend_from_slice(&self.inner.output_point.to_bytes_be());
+        PyBytes::new(py, &bytes)
+    }
+
+    #[getter]
+    fn polynomial_commitment<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.inner.polynomial_commitment.to_compressed())
+    }
+
+    #[getter]
+    fn quotient_commitment<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.inner.quotient_commitment.to_compressed())
+    }
+
+    #[getter]
+    fn output_point<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.inner.output_point.to_bytes_be())
+    }
+
+    fn verify(&self, input_point: &[u8], opening_key: &PyOpeningKey) -> PyResult<bool> {
+        Ok(self.inner.verify(scalar_from_bytes(input_point)?, &opening_key.inner))
+    }
+
+    fn __eq__(&self, other: &PyProof) -> bool {
+        self.inner.polynomial_commitment == other.inner.polynomial_commitment
+            && self.inner.quotient_commitment == other.inner.quotient_commitment
+            && self.inner.output_point == other.inner.output_point
+    }
+}
+
+/// The part of the trusted setup that is needed to verify proofs
+#[pyclass(name = "OpeningKey", module = "protodanksharding", frozen)]
+struct PyOpeningKey {
+    inner: OpeningKey,
+}
+
+#[pymethods]
+impl PyOpeningKey {
+    #[new]
+    fn new(g1_gen: &[u8], g2_gen: &[u8], tau_g2_gen: &[u8]) -> PyResult<Self> {
+        Ok(PyOpeningKey { inner: OpeningKey::new(g1_from_bytes(g1_gen)?, g2_from_bytes(g2_gen)?, g2_from_bytes(tau_g2_gen)?) })
+    }
+
+    /// Checks that the polynomial committed to evaluates to `output_point` at `input_point`
+    fn verify(
+        &self,
+        input_point: &[u8],
+        output_point: &[u8],
+        polynomial_commitment: &[u8],
+        quotient_commitment: &[u8],
+    ) -> PyResult<bool> {
+        Ok(self.inner.verify(
+            scalar_from_bytes(input_point)?,
+            scalar_from_bytes(output_point)?,
+            g1_from_bytes(polynomial_commitment)?,
+            g1_from_bytes(quotient_commitment)?,
+        ))
+    }
+}
+
+#[pymodule]
+fn protodanksharding(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyDomain>()?;
+    m.add_class::<PyPolynomial>()?;
+    m.add_class::<PyCommitKeyLagrange>()?;
+    m.add_class::<PyProof>()?;
+    m.add_class::<PyOpeningKey>()?;
+    m.add("DecodingError", m.py().get_type::<DecodingError>())?;
+    Ok(())
+}
+
+fn check_size(name: &str, size: usize, expected_name: &str, expected: usize) -> PyResult<()> {
+    if size != expected {
+        return Err(PyValueError::new_err(format!("number of {name} is {size}, but the {expected_name} is {expected}")));
+    }
+    Ok(())
+}
+
+fn scalar_from_bytes(bytes: &[u8]) -> PyResult<blstrs::Scalar> {
+    let bytes: &[u8; SCALAR_SERIALIZED_SIZE] = bytes.try_into().map_err(|_| {
+        DecodingError::new_err(format!("expected {SCALAR_SERIALIZED_SIZE} bytes for a field element, but received {}", bytes.len()))
+    })?;
+    Option::from(blstrs::Scalar::from_bytes_be(bytes))
+        .ok_or_else(|| DecodingError::new_err("field element is not smaller than the modulus"))
+}
+
+fn scalars_from_bytes(elements: &[Vec<u8>]) -> PyResult<Vec<blstrs::Scalar>> {
+    elements.iter().map(|element| scalar_from_bytes(element)).collect()
+}
+
+fn scalars_to_bytes<'py>(py: Python<'py>, scalars: &[blstrs::Scalar]) -> Vec<Bound<'py, PyBytes>> {
+    scalars.iter().map(|scalar| PyBytes::new(py, &scalar.to_bytes_be())).collect()
+}
+
+fn g1_from_bytes(bytes: &[u8]) -> PyResult<blstrs::G1Affine> {
+    let bytes: &[u8; G1_POINT_SERIALIZED_SIZE] = bytes.try_into().map_err(|_| {
+        DecodingError::new_err(format!("expected {G1_POINT_SERIALIZED_SIZE} bytes for a G1 point, but received {}", bytes.len()))
+    })?;
+    deserialize_g1_point(bytes).map_err(|error| DecodingError::new_err(error.to_string()))
+}
+
+fn g2_from_bytes(bytes: &[u8]) -> PyResult<blstrs::G2Affine> {
+    let bytes: &[u8; G2_POINT_SERIALIZED_SIZE] = bytes.try_into().map_err(|_| {
+        DecodingError::new_err(format!("expected {G2_POINT_SERIALIZED_SIZE} bytes for a G2 point, but received {}", bytes.len()))
+    })?;
+    // `from_compressed` also checks that the point is in the subgroup
+    Option::from(blstrs::G2Affine::from_compressed(bytes))
+        .ok_or_else(|| DecodingError::new_err("bytes are not a valid compressed G2 point"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::{ffi::c_str, types::PyDict, wrap_pymodule};
+    use rust_protodanksharding_example::params::PublicParameters;
+
+    // Runs `code` with the module imported and the setup for a domain of size 16 in `points` and `g2`
+    fn run_python(code: &std::ffi::CStr) {
+        let domain = Domain::new(16);
+        let params = PublicParameters::from_secret_insecure(1234, &domain);
+        let points: Vec<u8> = params.commit_key.points().iter().flat_map(|point| point.to_compressed()).collect();
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = wrap_pymodule!(protodanksharding)(py);
+            py.import("sys").unwrap().getattr("modules").unwrap().set_item("protodanksharding", module).unwrap();
+
+            let globals = PyDict::new(py);
+            globals.set_item("points", PyBytes::new(py, &points)).unwrap();
+            globals.set_item("g1", PyBytes::new(py, &params.opening_key.g1_gen.to_compressed())).unwrap();
+            globals.set_item("g2", PyBytes::new(py, &params.opening_key.g2_gen.to_compressed())).unwrap();
+            globals.set_item("tau_g2", PyBytes::new(py, &params.opening_key.tau_g2_gen.to_compressed())).unwrap();
+            if let Err(error) = py.run(code, Some(&globals), None) {
+                error.display(py);
+                panic!("python code failed: {error}");
+            }
+        });
+    }
+
+    #[test]
+    fn commit_prove_and_verify() {
+        run_python(c_str!(
+            r#"
+from protodanksharding import CommitKeyLagrange, DecodingError, Domain, OpeningKey, Polynomial, Proof
+
+def scalar(value):
+    return value.to_bytes(32, "big")
+
+domain = Domain(16)
+polynomial = Polynomial([scalar(3 * index + 1) for index in range(16)])
+assert len(domain) == 16 and len(polynomial) == 16
+assert Polynomial.from_blob(polynomial.to_blob()) == polynomial
+assert domain.fft(domain.ifft(polynomial.evaluations)) == polynomial.evaluations
+assert polynomial.evaluate(domain.roots()[5], domain) == scalar(16)
+
+commit_key = CommitKeyLagrange(points)
+commitment = commit_key.commit(polynomial)
+commit_key.precompute(4)
+assert commit_key.commit(polynomial) == commitment
+
+opening_key = OpeningKey(g1, g2, tau_g2)
+proof = Proof.create(commit_key, polynomial, commitment, scalar(1000), domain)
+assert proof.output_point == polynomial.evaluate(scalar(1000), domain)
+assert proof.verify(scalar(1000), opening_key)
+assert not proof.verify(scalar(1001), opening_key)
+assert opening_key.verify(scalar(1000), proof.output_point, commitment, proof.quotient_commitment)
+assert Proof.from_bytes(proof.to_bytes()) == proof
+"#
+        ));
+    }
+
+    #[test]
+    fn invalid_arguments_raise_value_errors() {
+        run_python(c_str!(
+            r#"
+from protodanksharding import CommitKeyLagrange, DecodingError, Domain, Polynomial, Proof
+
+def raises(error, function, *args):
+    try:
+        function(*args)
+    except error:
+        return
+    raise AssertionError(f"{function} did not raise {error}")
+
+assert issubclass(DecodingError, ValueError)
+modulus = 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001
+raises(DecodingError, Polynomial, [modulus.to_bytes(32, "big")])
+raises(DecodingError, Polynomial, [bytes(31)])
+raises(ValueError, Polynomial, [bytes(32)] * 3)
+raises(ValueError, Polynomial.from_blob, bytes(33))
+raises(ValueError, Domain, 0)
+raises(DecodingError, CommitKeyLagrange, bytes(len(points)))
+raises(ValueError, CommitKeyLagrange, points[:-1])
+raises(ValueError, CommitKeyLagrange(points).commit, Polynomial([bytes(32)] * 8))
+raises(ValueError, CommitKeyLagrange(points).precompute, 1)
+raises(DecodingError, Proof.from_bytes, bytes(128))
+raises(ValueError, Polynomial([bytes(32)] * 8).evaluate, bytes(32), Domain(16))
+"#
+        ));
+    }
+}
@@ -0,0 +1,168 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{
+    commit_key::Commitment, domain::Domain, encoding::PayloadCodec, multi_proof::MultiPointProof,
+    params::PublicParameters, polynomial::Polynomial,
+};
+
+/// Errors that can occur when proving or verifying a range of bytes in a blob
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ByteRangeError {
+    /// The range is empty, or not within the bytes of a blob
+    InvalidRange { byte_range: Range<usize>, bytes_per_blob: usize },
+    /// The blob does not have the number of field elements that the codec uses
+    BlobSizeMismatch { blob_size: usize, polynomial_size: usize },
+    /// The range covers more field elements than there are powers of tau in G2 for
+    TooManyFieldElements { num_field_elements: usize, max_field_elements: usize },
+}
+
+impl core::fmt::Display for ByteRangeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ByteRangeError::InvalidRange { byte_range, bytes_per_blob } => {
+                write!(f, "byte range {byte_range:?} is empty or not within the {bytes_per_blob} bytes of a blob")
+            }
+            ByteRangeError::BlobSizeMismatch { blob_size, polynomial_size } => write!(
+                f,
+                "blob has {polynomial_size} field elements, but blobs have {blob_size} field elements"
+            ),
+            ByteRangeError::TooManyFieldElements { num_field_elements, max_field_elements } => write!(
+                f,
+                "byte range covers {num_field_elements} field elements, but at most {max_field_elements} can be opened"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for ByteRangeError {}
+
+/// A proof that the bytes in a range of a blob have particular values
+///
+/// This is a single opening of the blob at the roots of unity of the field elements that hold the bytes.
+pub struct ByteRangeProof {
+    /// The indices of the field elements that were opened
+    pub field_elements: Range<usize>,
+    pub proof: MultiPointProof,
+}
+
+/// Proves the bytes `byte_range` of a blob that was encoded with `codec`
+///
+/// The range is an offset into the `bytes_per_blob` bytes of the blob, `PayloadCodec::locate_payload_range`
+/// maps a range of the payload to these.
+pub fn prove_byte_range(
+    public_parameters: &PublicParameters,
+    codec: &PayloadCodec,
+    blob: &Polynomial,
+    commitment: Commitment,
+    byte_range: Range<usize>,
+) -> Result<ByteRangeProof, ByteRangeError> {
+    if blob.evaluations.len() != codec.blob_size() {
+        return Err(ByteRangeError::BlobSizeMismatch {
+            blob_size: codec.blob_size(),
+            polynomial_size: blob.evaluations.len(),
+        });
+    }
+    let field_elements = covering_field_elements(public_parameters, codec, &byte_range)?;
+
+    let indices: Vec<_> = field_elements.clone().collect();
+    let domain = Domain::get(codec.blob_size());
+    let proof = MultiPointProof::create(public_parameters, blob, commitment, &indices, &domain);
+    Ok(ByteRangeProof { field_elements, proof })
+}
+
+/// Verifies that the bytes `byte_range` of the blob with the given commitment are `bytes`
+///
+/// Returns false, if the proof is for another commitment or range, or the opened field elements do
+/// not decode to `bytes`.
+pub fn verify_byte_range(
+    public_parameters: &PublicParameters,
+    codec: &PayloadCodec,
+    commitment: Commitment,
+    byte_range: Range<usize>,
+    bytes: &[u8],
+    proof: &ByteRangeProof,
+) -> Result<bool, ByteRangeError> {
+    let field_elements = covering_field_elements(public_parameters, codec, &byte_range)?;
+    if proof.field_elements != field_elements || proof.proof.polynomial_commitment != commitment {
+        return Ok(false);
+    }
+
+    match codec.unpack_byte_range(byte_range, &proof.proof.output_points) {
+        Some(decoded) if decoded == bytes => {}
+        _ => return Ok(false),
+    }
+
+    let indices: Vec<_> = field_elements.collect();
+    let domain = Domain::get(codec.blob_size());
+    Ok(proof.proof.verify(&indices, public_parameters, &domain))
+}
+
+// Checks the range, and returns the indices of the field elements that hold it
+fn covering_field_elements(
+    public_parameters: &PublicParameters,
+    codec: &PayloadCodec,
+    byte_range: &Range<usize>,
+) -> Result<Range<usize>, ByteRangeError> {
+    let bytes_per_blob = codec.bytes_per_blob();
+    if byte_range.is_empty() || byte_range.end > bytes_per_blob {
+        return Err(ByteRangeError::InvalidRange { byte_range: byte_range.clone(), bytes_per_blob });
+    }
+
+    let field_elements = codec.field_element_range(byte_range.clone());
+    // Opening `k` points needs `[\tau^k]_2`
+    let max_field_elements = public_parameters.g2_monomial.len().saturating_sub(1);
+    if field_elements.len() > max_field_elements {
+        return Err(ByteRangeError::TooManyFieldElements {
+            num_field_elements: field_elements.len(),
+            max_field_elements,
+        });
+    }
+    Ok(field_elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::Packing;
+
+    use super::*;
+
+    #[test]
+    fn byte_range_proofs_verify_decoded_bytes() {
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &Domain::new(16));
+
+        for packing in [Packing::Bytes31, Packing::Bits254] {
+            let codec = PayloadCodec::new(16, packing);
+            let payload: Vec<u8> = (0..300).map(|index| (index * 7 + 3) as u8).collect();
+            let blobs = codec.encode_payload(&payload);
+            let commitment = public_parameters.commit_key.commit(&blobs[0]);
+
+            // The payload bytes 50..110, after the 8 byte length prefix
+            let byte_range = 50 + 8..110 + 8;
+            assert_eq!(codec.locate_payload_range(50..110), vec![(0, byte_range.clone())]);
+            let proof = prove_byte_range(&public_parameters, &codec, &blobs[0], commitment, byte_range.clone()).unwrap();
+            let bytes = &payload[50..110];
+            assert!(verify_byte_range(&public_parameters, &codec, commitment, byte_range.clone(), bytes, &proof).unwrap());
+
+            let mut wrong_bytes = bytes.to_vec();
+            wrong_bytes[10] ^= 1;
+            assert!(!verify_byte_range(&public_parameters, &codec, commitment, byte_range.clone(), &wrong_bytes, &proof).unwrap());
+
+            // The same field elements hold the shifted range, but the bytes there are different
+            let shifted = byte_range.start + 1..byte_range.end + 1;
+            assert!(verify_byte_range(&public_parameters, &codec, commitment, shifted.clone(), &payload[51..111], &proof).unwrap());
+            assert!(!verify_byte_range(&public_parameters, &codec, commitment, shifted, bytes, &proof).unwrap());
+
+            let other_commitment = public_parameters.commit_key.commit(&codec.encode_payload(&payload[1..])[0]);
+            assert!(!verify_byte_range(&public_parameters, &codec, other_commitment, byte_range, bytes, &proof).unwrap());
+        }
+
+        let codec = PayloadCodec::new(16, Packing::Bytes31);
+        let blob = &codec.encode_payload(&[1, 2, 3])[0];
+        let commitment = public_parameters.commit_key.commit(blob);
+        assert_eq!(
+            prove_byte_range(&public_parameters, &codec, blob, commitment, 10..10).err(),
+            Some(ByteRangeError::InvalidRange { byte_range: 10..10, bytes_per_blob: 16 * 31 })
+        );
+    }
+}
@@ -0,0 +1,611 @@
+use alloc::{sync::Arc, vec, vec::Vec};
+
+use ff::{Field, PrimeField};
+use group::prime::PrimeCurveAffine;
+use pairing_lib::{group::Group, MillerLoopResult, MultiMillerLoop};
+use rand_core::RngCore;
+
+use crate::{
+    commit_key::{g1_lincomb, Commitment},
+    domain::Domain,
+    params::PublicParameters,
+    polynomial::Polynomial,
+    utils::{reverse_bit_order, reverse_bits, serial_batch_inversion},
+};
+
+/// The evaluations of the extended blob on one coset, in bit reversed order
+pub type Cell = Vec<blstrs::Scalar>;
+/// A KZG proof for all of the evaluations in a cell
+pub type CellProof = blstrs::G1Affine;
+
+/// Errors that can occur when computing, verifying or recovering cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellError {
+    /// The cell size must be a power of two that is smaller than the blob
+    InvalidCellSize { cell_size: usize, blob_size: usize },
+    /// The SRS has fewer G1 points than the blob has field elements
+    BlobTooLarge { blob_size: usize, srs_size: usize },
+    /// The SRS does not contain `\tau^k G2` for cells of size `k`
+    MissingG2Power { cell_size: usize, g2_srs_size: usize },
+    /// The blob does not have one evaluation per element in the domain
+    BlobSizeMismatch { blob_size: usize, polynomial_size: usize },
+    /// A cell does not have one evaluation per element in its coset
+    CellSizeMismatch { cell_size: usize, received: usize },
+    /// The cell index is not smaller than the number of cells in an extended blob
+    InvalidCellIndex { cell_index: usize, num_cells: usize },
+    /// The inputs do not have the same number of entries
+    LengthMismatch,
+    /// The same cell was passed more than once
+    DuplicateCellIndex { cell_index: usize },
+    /// At least half of the cells are needed to recover the blob
+    NotEnoughCells { received: usize, required: usize },
+    /// The cells are not evaluations of a polynomial of the blob's degree
+    InconsistentCells,
+}
+
+impl core::fmt::Display for CellError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CellError::InvalidCellSize { cell_size, blob_size } => write!(
+                f,
+                "cell size {cell_size} must be a power of two smaller than the blob size {blob_size}"
+            ),
+            CellError::BlobTooLarge { blob_size, srs_size } => write!(
+                f,
+                "blob has {blob_size} field elements, but the SRS only has {srs_size} points"
+            ),
+            CellError::MissingG2Power { cell_size, g2_srs_size } => write!(
+                f,
+                "cells of size {cell_size} need tau^{cell_size} in G2, but the SRS only has {g2_srs_size} G2 points"
+            ),
+            CellError::BlobSizeMismatch { blob_size, polynomial_size } => write!(
+                f,
+                "polynomial has {polynomial_size} evaluations, but a blob has {blob_size} field elements"
+            ),
+            CellError::CellSizeMismatch { cell_size, received } => {
+                write!(f, "cell has {received} evaluations, but cells have {cell_size} field elements")
+            }
+            CellError::InvalidCellIndex { cell_index, num_cells } => {
+                write!(f, "cell index {cell_index} is out of range, there are {num_cells} cells")
+            }
+            CellError::LengthMismatch => write!(f, "inputs do not have the same length"),
+            CellError::DuplicateCellIndex { cell_index } => write!(f, "cell {cell_index} was passed more than once"),
+            CellError::NotEnoughCells { received, required } => {
+                write!(f, "received {received} cells, but at least {required} are needed for recovery")
+            }
+            CellError::InconsistentCells => write!(f, "cells are not evaluations of the same blob"),
+        }
+    }
+}
+
+impl core::error::Error for CellError {}
+
+/// Splits extended blobs into cells, and creates and verifies proofs for them (EIP-7594)
+///
+/// A blob of `n` field elements is the polynomial `p` of degree less than `n`, given by its evaluations
+/// over the blob domain. It is extended to its `2n` evaluations over the domain of size `2n`, which are
+/// split into cells of `k` evaluations. As in the specs, the extended evaluations are in bit reversed
+/// order, so every cell is a coset `h * H` of the subgroup `H` of size `k`, and the first half of
+/// the cells is the blob itself, in bit reversed order.
+///
+/// The proof for a cell is a commitment to the quotient `(p(X) - I(X)) / (X^k - h^k)`, where `I` is the
+/// polynomial of degree less than `k` that interpolates the cell.
+pub struct CellContext {
+    blob_domain: Arc<Domain>,
+    extended_domain: Arc<Domain>,
+    // The subgroup `H`, every cell is a coset of it
+    cell_domain: Arc<Domain>,
+    // The cosets have shifts `h_i` with `h_i^k` being the roots of unity of this size
+    cosets_domain: Arc<Domain>,
+    // The first `k` powers of \tau in G1, to commit to the interpolation polynomials of cells
+    interpolation_points: Vec<blstrs::G1Affine>,
+    // For every frequency `j` of the circulant matrices, the `k` points that the transformed
+    // coefficients are multiplied with, see `compute_cell_proofs`
+    toeplitz_points: Vec<Vec<blstrs::G1Affine>>,
+    prepared_g2: blstrs::G2Prepared,
+    // \tau^k times the generator of G2
+    prepared_tau_pow_cell_size_g2: blstrs::G2Prepared,
+}
+
+impl CellContext {
+    /// Creates the context for blobs of `blob_size` field elements, which are split into cells of
+    /// `cell_size` field elements after being extended
+    ///
+    /// This precomputes the FFTs of the SRS that are used to create all of the cell proofs at once.
+    pub fn new(public_parameters: &PublicParameters, blob_size: usize, cell_size: usize) -> Result<Self, CellError> {
+        if !blob_size.is_power_of_two() || !cell_size.is_power_of_two() || cell_size >= blob_size {
+            return Err(CellError::InvalidCellSize { cell_size, blob_size });
+        }
+        let monomial_points = public_parameters.commit_key_monomial.points();
+        if monomial_points.len() < blob_size {
+            return Err(CellError::BlobTooLarge { blob_size, srs_size: monomial_points.len() });
+        }
+        let g2_srs_size = public_parameters.g2_monomial.len();
+        if g2_srs_size <= cell_size {
+            return Err(CellError::MissingG2Power { cell_size, g2_srs_size });
+        }
+
+        let extended_size = 2 * blob_size;
+        let num_cells = extended_size / cell_size;
+
+        Ok(CellContext {
+            blob_domain: Domain::get(blob_size),
+            extended_domain: Domain::get(extended_size),
+            cell_domain: Domain::get(cell_size),
+            cosets_domain: Domain::get(num_cells),
+            interpolation_points: monomial_points[..cell_size].to_vec(),
+            toeplitz_points: precompute_toeplitz_points(&monomial_points[..blob_size], cell_size),
+            prepared_g2: public_parameters.opening_key.prepared_g2.clone(),
+            prepared_tau_pow_cell_size_g2: blstrs::G2Prepared::from(public_parameters.g2_monomial[cell_size]),
+        })
+    }
+
+    pub fn blob_size(&self) -> usize {
+        self.blob_domain.size()
+    }
+
+    pub fn cell_size(&self) -> usize {
+        self.cell_domain.size()
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.cosets_domain.size()
+    }
+
+    /// Extends the blob and splits it into cells
+    pub fn compute_cells(&self, blob: &Polynomial) -> Result<Vec<Cell>, CellError> {
+        let coefficients = self.blob_to_coefficients(blob)?;
+        Ok(self.cells_from_coefficients(coefficients))
+    }
+
+    /// Extends the blob, splits it into cells and creates a proof for every cell
+    pub fn compute_cells_and_kzg_proofs(&self, blob: &Polynomial) -> Result<(Vec<Cell>, Vec<CellProof>), CellError> {
+        let coefficients = self.blob_to_coefficients(blob)?;
+        let proofs = self.compute_cell_proofs(&coefficients);
+        Ok((self.cells_from_coefficients(coefficients), proofs))
+    }
+
+    /// Verifies many cell proofs at once, for any mix of blobs and cell indices
+    ///
+    /// `commitments[i]` is the commitment to the blob that `cells[i]` is a cell of, and `proofs[i]`
+    /// is its proof. The proofs are combined with random powers of `r`, so that all of them are
+    /// checked with two pairings. The interpolation polynomials of cells with the same index are
+    /// combined before they are interpolated, so the cost of the interpolation depends on the number
+    /// of distinct cell indices, not on the number of cells.
+    ///
+    /// Note: This is a probabilistic check, `rng` must be a cryptographically secure rng.
+    pub fn verify_cell_kzg_proof_batch(
+        &self,
+        commitments: &[Commitment],
+        cell_indices: &[usize],
+        cells: &[Cell],
+        proofs: &[CellProof],
+        mut rng: impl RngCore,
+    ) -> Result<bool, CellError> {
+        if commitments.len() != cells.len() || cell_indices.len() != cells.len() || proofs.len() != cells.len() {
+            return Err(CellError::LengthMismatch);
+        }
+        for (cell_index, cell) in cell_indices.iter().zip(cells) {
+            self.check_cell(*cell_index, cell)?;
+        }
+        if cells.is_empty() {
+            return Ok(true);
+        }
+
+        let r = blstrs::Scalar::random(&mut rng);
+        let powers_of_r: Vec<_> = core::iter::successors(Some(blstrs::Scalar::one()), |power| Some(power * r))
+            .take(cells.len())
+            .collect();
+
+        // \sum r^i * C_i
+        let commitments_sum = g1_lincomb(commitments, &powers_of_r);
+
+        // \sum r^i * h_i^k * \pi_i
+        let shifted_powers_of_r: Vec<_> = cell_indices
+            .iter()
+            .zip(&powers_of_r)
+            .map(|(cell_index, power_of_r)| self.coset_shift_pow_cell_size(*cell_index) * power_of_r)
+            .collect();
+        let shifted_proofs_sum = g1_lincomb(proofs, &shifted_powers_of_r);
+
+        // \sum r^i * \pi_i
+        let proofs_sum = g1_lincomb(proofs, &powers_of_r);
+
+        // \sum r^i * I_i(\tau), summing up the evaluations of cells with the same index first
+        let mut aggregated_cells = vec![None; self.num_cells()];
+        for ((cell_index, cell), power_of_r) in cell_indices.iter().zip(cells).zip(&powers_of_r) {
+            let aggregated_cell = aggregated_cells[*cell_index].get_or_insert_with(|| vec![blstrs::Scalar::zero(); self.cell_size()]);
+            for (aggregated, evaluation) in aggregated_cell.iter_mut().zip(cell) {
+                *aggregated += evaluation * power_of_r;
+            }
+        }
+        let mut interpolation_coefficients = vec![blstrs::Scalar::zero(); self.cell_size()];
+        for (cell_index, aggregated_cell) in aggregated_cells.into_iter().enumerate() {
+            if let Some(aggregated_cell) = aggregated_cell {
+                let coefficients = self.interpolate_cell(cell_index, aggregated_cell);
+                for (sum, coefficient) in interpolation_coefficients.iter_mut().zip(coefficients) {
+                    *sum += coefficient;
+                }
+            }
+        }
+        let interpolation_sum = g1_lincomb(&self.interpolation_points, &interpolation_coefficients);
+
+        // Each proof satisfies e(C_i - I_i(\tau) + h_i^k * \pi_i, G2) = e(\pi_i, \tau^k * G2)
+        let lhs: blstrs::G1Affine =
+            (blstrs::G1Projective::from(commitments_sum) - interpolation_sum + shifted_proofs_sum).into();
+        let neg_proofs_sum = -proofs_sum;
+
+        let terms = [(&lhs, &self.prepared_g2), (&neg_proofs_sum, &self.prepared_tau_pow_cell_size_g2)];
+        let pairing = blstrs::Bls12::multi_miller_loop(&terms).final_exponentiation();
+
+        Ok(pairing.is_identity().into())
+    }
+
+    /// Recovers all of the cells and their proofs from at least half of the cells
+    ///
+    /// The blob is recovered from the extended evaluations, with the polynomial that vanishes on the
+    /// missing cosets, see `recover_coefficients`.
+    pub fn recover_cells_and_kzg_proofs(
+        &self,
+        cell_indices: &[usize],
+        cells: &[Cell],
+    ) -> Result<(Vec<Cell>, Vec<CellProof>), CellError> {
+        if cell_indices.len() != cells.len() {
+            return Err(CellError::LengthMismatch);
+        }
+        let mut received = vec![false; self.num_cells()];
+        for (cell_index, cell) in cell_indices.iter().zip(cells) {
+            self.check_cell(*cell_index, cell)?;
+            if core::mem::replace(&mut received[*cell_index], true) {
+                return Err(CellError::DuplicateCellIndex { cell_index: *cell_index });
+            }
+        }
+        let required = self.num_cells() / 2;
+        if cells.len() < required {
+            return Err(CellError::NotEnoughCells { received: cells.len(), required });
+        }
+
+        let extended_size = self.extended_domain.size();
+        let cell_size = self.cell_size();
+
+        // The extended evaluations in bit reversed order, with zeros for the missing cells
+        let mut extended_evaluations = vec![blstrs::Scalar::zero(); extended_size];
+        for (cell_index, cell) in cell_indices.iter().zip(cells) {
+            extended_evaluations[cell_index * cell_size..][..cell_size].copy_from_slice(cell);
+        }
+        reverse_bit_order(&mut extended_evaluations);
+
+        let missing_roots = (0..self.num_cells())
+            .filter(|cell_index| !received[*cell_index])
+            .map(|cell_index| self.coset_shift_pow_cell_size(cell_index));
+        let vanishing_polynomial = vanishing_polynomial_of_cosets(missing_roots, cell_size, extended_size);
+
+        let coefficients =
+            recover_coefficients(&self.extended_domain, extended_evaluations, vanishing_polynomial, self.blob_size())
+                .ok_or(CellError::InconsistentCells)?;
+
+        let proofs = self.compute_cell_proofs(&coefficients);
+        Ok((self.cells_from_coefficients(coefficients), proofs))
+    }
+
+    fn blob_to_coefficients(&self, blob: &Polynomial) -> Result<Vec<blstrs::Scalar>, CellError> {
+        let polynomial_size = blob.evaluations.len();
+        if polynomial_size != self.blob_size() {
+            return Err(CellError::BlobSizeMismatch { blob_size: self.blob_size(), polynomial_size });
+        }
+        Ok(self.blob_domain.ifft_scalars(blob.evaluations.clone()))
+    }
+
+    fn check_cell(&self, cell_index: usize, cell: &Cell) -> Result<(), CellError> {
+        if cell_index >= self.num_cells() {
+            return Err(CellError::InvalidCellIndex { cell_index, num_cells: self.num_cells() });
+        }
+        if cell.len() != self.cell_size() {
+            return Err(CellError::CellSizeMismatch { cell_size: self.cell_size(), received: cell.len() });
+        }
+        Ok(())
+    }
+
+    fn cells_from_coefficients(&self, mut coefficients: Vec<blstrs::Scalar>) -> Vec<Cell> {
+        coefficients.resize(self.extended_domain.size(), blstrs::Scalar::zero());
+        let mut extended_evaluations = self.extended_domain.fft_scalars(coefficients);
+        reverse_bit_order(&mut extended_evaluations);
+        extended_evaluations.chunks_exact(self.cell_size()).map(|cell| cell.to_vec()).collect()
+    }
+
+    // Cell `i` is the coset with the shift `h_i = w^brp(i)`, where `w` generates the extended domain
+    // and `brp` reverses the bits of the index among the cells. `h_i^k` is then the `brp(i)`-th
+    // root of unity of order `num_cells`.
+    fn coset_shift(&self, cell_index: usize) -> blstrs::Scalar {
+        self.extended_domain.roots[reverse_bits(cell_index, self.num_cells())]
+    }
+
+    fn coset_shift_pow_cell_size(&self, cell_index: usize) -> blstrs::Scalar {
+        self.cosets_domain.roots[reverse_bits(cell_index, self.num_cells())]
+    }
+
+    // Returns the coefficients of the polynomial of degree less than `k` that takes the values of
+    // the cell on its coset
+    fn interpolate_cell(&self, cell_index: usize, mut cell: Cell) -> Vec<blstrs::Scalar> {
+        // The cell holds the evaluations of I(h * X) over H, in bit reversed order
+        reverse_bit_order(&mut cell);
+        let shifted_coefficients = self.cell_domain.ifft_scalars(cell);
+        scale_coefficients(shifted_coefficients, self.coset_shift(cell_index).invert().unwrap())
+    }
+
+    // Computes the proofs for all of the cells at once, using the FK20 method
+    //
+    // With `p(X) = \sum f_i X^i`, `m = n / k` and `s = h^k`, the quotient of `p(X) - I(X)` by
+    // `X^k - s` evaluated at \tau is `\sum_{u=0}^{m-2} s^u * H_u`, where
+    //  H_u = \sum_{r=0}^{k-1} \sum_{t=u+1}^{m-1} f_{t*k+r} * \tau^{r + k * (t-1-u)} G1
+    //
+    // So the proofs are the evaluations of a polynomial with the coefficients `H_u` at the roots
+    // of unity `h_i^k`, which is a single FFT in G1. For each `r`, the sum over `t` is a product
+    // of a Toeplitz matrix of SRS points with the coefficients `f_{t*k+r}`. These are computed as
+    // convolutions with FFTs of size `2m`, where the FFTs of the SRS points are precomputed, and
+    // the products for all `r` are summed up before the inverse FFT.
+    fn compute_cell_proofs(&self, coefficients: &[blstrs::Scalar]) -> Vec<CellProof> {
+        let cell_size = self.cell_size();
+        let num_cells = self.num_cells();
+        let m = self.blob_size() / cell_size;
+        let circulant_domain = &self.cosets_domain;
+
+        // The coefficients f_{(m-1-i)*k+r} for i < m, padded with zeros, for each `r`
+        let transformed_coefficients: Vec<Vec<blstrs::Scalar>> = (0..cell_size)
+            .map(|offset| {
+                let mut column: Vec<_> = (0..m).rev().map(|t| coefficients[t * cell_size + offset]).collect();
+                column.resize(num_cells, blstrs::Scalar::zero());
+                circulant_domain.fft_scalars(column)
+            })
+            .collect();
+
+        let products: Vec<_> = self
+            .toeplitz_points
+            .iter()
+            .enumerate()
+            .map(|(frequency, points)| {
+                let scalars: Vec<_> = transformed_coefficients.iter().map(|column| column[frequency]).collect();
+                g1_lincomb(points, &scalars)
+            })
+            .collect();
+        let convolution = circulant_domain.ifft_g1(products);
+
+        // H_u is at index m-2-u of the convolution
+        let mut h: Vec<_> = (0..m.saturating_sub(1)).map(|u| convolution[m - 2 - u]).collect();
+        h.resize(num_cells, blstrs::G1Affine::identity());
+
+        let mut proofs = circulant_domain.fft_g1(h);
+        reverse_bit_order(&mut proofs);
+        proofs
+    }
+}
+
+// Returns the `size` coefficients of Z(X) = \prod (X^k - s_i), which vanishes on the cosets `h_i * H`
+// of the subgroup of size `k`, where `s_i = h_i^k`
+//
+// Z is a polynomial in X^k, so it is built up one factor at a time in the coefficients of X^k.
+pub(crate) fn vanishing_polynomial_of_cosets(
+    roots: impl Iterator<Item = blstrs::Scalar>,
+    coset_size: usize,
+    size: usize,
+) -> Vec<blstrs::Scalar> {
+    let mut vanishing_polynomial = vec![blstrs::Scalar::zero(); size];
+    vanishing_polynomial[0] = blstrs::Scalar::one();
+    let mut degree = 0;
+    for root in roots {
+        degree += coset_size;
+        for index in (coset_size..=degree).rev().step_by(coset_size) {
+            let previous = vanishing_polynomial[index - coset_size];
+            vanishing_polynomial[index] = previous - root * vanishing_polynomial[index];
+        }
+        vanishing_polynomial[0] = -root * vanishing_polynomial[0];
+    }
+    vanishing_polynomial
+}
+
+// Recovers the coefficients of a polynomial `p` of degree less than `degree_bound` from its evaluations
+// over `domain` in natural order, where the missing evaluations are zero and `vanishing_polynomial`
+// vanishes exactly on the missing points
+//
+// Let `E` be the evaluations, then `E * Z = p * Z` on the domain, and since `p * Z` has degree less than
+// the size of the domain it is recovered with an inverse FFT. `p` is `(p * Z) / Z`, which is computed
+// on a coset of the domain where `Z` has no roots.
+//
+// Returns `None`, if the evaluations do not come from a polynomial of degree less than `degree_bound`.
+pub(crate) fn recover_coefficients(
+    domain: &Domain,
+    evaluations: Vec<blstrs::Scalar>,
+    vanishing_polynomial: Vec<blstrs::Scalar>,
+    degree_bound: usize,
+) -> Option<Vec<blstrs::Scalar>> {
+    // (p * Z)(X), by interpolating E * Z over the domain
+    let vanishing_evaluations = domain.fft_scalars(vanishing_polynomial.clone());
+    let product_evaluations = evaluations
+        .iter()
+        .zip(&vanishing_evaluations)
+        .map(|(evaluation, vanishing)| evaluation * vanishing)
+        .collect();
+    let product = domain.ifft_scalars(product_evaluations);
+
+    // p(X) = (p * Z)(X) / Z(X), on the coset g * D where g is not a root of unity
+    let shift = blstrs::Scalar::multiplicative_generator();
+    let product_on_coset = domain.fft_scalars(scale_coefficients(product, shift));
+    let mut vanishing_on_coset = domain.fft_scalars(scale_coefficients(vanishing_polynomial, shift));
+    serial_batch_inversion(&mut vanishing_on_coset);
+    let quotient_on_coset = product_on_coset
+        .iter()
+        .zip(&vanishing_on_coset)
+        .map(|(product, vanishing_inv)| product * vanishing_inv)
+        .collect();
+    let mut coefficients = scale_coefficients(domain.ifft_scalars(quotient_on_coset), shift.invert().unwrap());
+
+    // The division is only exact if the evaluations came from a polynomial of degree less than the bound
+    if coefficients[degree_bound..].iter().any(|coefficient| !bool::from(coefficient.is_zero())) {
+        return None;
+    }
+    coefficients.truncate(degree_bound);
+    Some(coefficients)
+}
+
+// For each `r < k`, the FFT of size `2m` of the points `\tau^{r + k * i} G1` for `i < m`, padded with zeros.
+// The result is transposed, so that the points for each frequency are next to each other.
+fn precompute_toeplitz_points(monomial_points: &[blstrs::G1Affine], cell_size: usize) -> Vec<Vec<blstrs::G1Affine>> {
+    let m = monomial_points.len() / cell_size;
+    let circulant_domain = Domain::get(2 * m);
+
+    let columns: Vec<_> = (0..cell_size)
+        .map(|offset| {
+            let mut column: Vec<_> = monomial_points[offset..].iter().step_by(cell_size).copied().collect();
+            column.resize(2 * m, blstrs::G1Affine::identity());
+            circulant_domain.fft_g1(column)
+        })
+        .collect();
+
+    (0..2 * m).map(|frequency| columns.iter().map(|column| column[frequency]).collect()).collect()
+}
+
+// Returns the coefficients of p(a * X), given the coefficients of p(X)
+fn scale_coefficients(mut coefficients: Vec<blstrs::Scalar>, factor: blstrs::Scalar) -> Vec<blstrs::Scalar> {
+    let mut power = blstrs::Scalar::one();
+    for coefficient in coefficients.iter_mut() {
+        *coefficient *= power;
+        power *= factor;
+    }
+    coefficients
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils;
+
+    use super::*;
+
+    const BLOB_SIZE: usize = 32;
+    const CELL_SIZE: usize = 4;
+
+    fn setup() -> (PublicParameters, CellContext) {
+        let public_parameters = test_utils::public_parameters(BLOB_SIZE);
+        let context = CellContext::new(&public_parameters, BLOB_SIZE, CELL_SIZE).unwrap();
+        (public_parameters, context)
+    }
+
+    fn random_blob() -> Polynomial {
+        test_utils::random_blob(BLOB_SIZE)
+    }
+
+    #[test]
+    fn cells_are_evaluations_on_cosets() {
+        let (_, context) = setup();
+        let blob = random_blob();
+        let cells = context.compute_cells(&blob).unwrap();
+        assert_eq!(cells.len(), 2 * BLOB_SIZE / CELL_SIZE);
+
+        let domain = Domain::new(BLOB_SIZE);
+        for (cell_index, cell) in cells.iter().enumerate() {
+            let shift = context.coset_shift(cell_index);
+            for (position, evaluation) in cell.iter().enumerate() {
+                let point = shift * context.cell_domain.roots[reverse_bits(position, CELL_SIZE)];
+                assert_eq!(*evaluation, blob.evaluate(point, &domain));
+            }
+        }
+
+        // The first half of the cells is the blob in bit reversed order
+        let mut blob_evaluations = blob.evaluations.clone();
+        reverse_bit_order(&mut blob_evaluations);
+        assert_eq!(cells[..cells.len() / 2].concat(), blob_evaluations);
+    }
+
+    #[test]
+    fn proofs_match_quotient_commitments() {
+        let (public_parameters, context) = setup();
+        let blob = random_blob();
+        let (cells, proofs) = context.compute_cells_and_kzg_proofs(&blob).unwrap();
+        let coefficients = blob.to_coefficients();
+
+        for (cell_index, (cell, proof)) in cells.iter().zip(&proofs).enumerate() {
+            // (p(X) - I(X)) / (X^k - s), by long division of p, since I is the remainder
+            let s = context.coset_shift_pow_cell_size(cell_index);
+            let mut remainder = coefficients.clone();
+            let mut quotient = vec![blstrs::Scalar::zero(); BLOB_SIZE - CELL_SIZE];
+            for index in (CELL_SIZE..BLOB_SIZE).rev() {
+                let leading = remainder[index];
+                quotient[index - CELL_SIZE] = leading;
+                remainder[index - CELL_SIZE] += s * leading;
+            }
+            assert_eq!(remainder[..CELL_SIZE], context.interpolate_cell(cell_index, cell.clone()));
+
+            let expected = g1_lincomb(&public_parameters.commit_key_monomial.points()[..quotient.len()], &quotient);
+            assert_eq!(*proof, expected);
+        }
+    }
+
+    #[test]
+    fn verify_cells_from_many_blobs() {
+        let (public_parameters, context) = setup();
+
+        let mut commitments = Vec::new();
+        let mut cell_indices = Vec::new();
+        let mut cells = Vec::new();
+        let mut proofs = Vec::new();
+        for _ in 0..3 {
+            let blob = random_blob();
+            let commitment = public_parameters.commit_key.commit(&blob);
+            let (blob_cells, blob_proofs) = context.compute_cells_and_kzg_proofs(&blob).unwrap();
+            for cell_index in [0, 3, 7, 15] {
+                commitments.push(commitment);
+                cell_indices.push(cell_index);
+                cells.push(blob_cells[cell_index].clone());
+                proofs.push(blob_proofs[cell_index]);
+            }
+        }
+        let verify = |cells: &[Cell], proofs: &[CellProof]| {
+            context.verify_cell_kzg_proof_batch(&commitments, &cell_indices, cells, proofs, rand::thread_rng())
+        };
+        assert!(verify(&cells, &proofs).unwrap());
+
+        let mut wrong_cells = cells.clone();
+        wrong_cells[5][1] += blstrs::Scalar::one();
+        assert!(!verify(&wrong_cells, &proofs).unwrap());
+
+        let mut wrong_proofs = proofs.clone();
+        wrong_proofs.swap(0, 1);
+        assert!(!verify(&cells, &wrong_proofs).unwrap());
+
+        assert_eq!(verify(&cells, &proofs[1..]), Err(CellError::LengthMismatch));
+
+        let mut invalid_indices = cell_indices.clone();
+        invalid_indices[0] = 16;
+        assert_eq!(
+            context.verify_cell_kzg_proof_batch(&commitments, &invalid_indices, &cells, &proofs, rand::thread_rng()),
+            Err(CellError::InvalidCellIndex { cell_index: 16, num_cells: 16 })
+        );
+    }
+
+    #[test]
+    fn recover_from_half_of_the_cells() {
+        let (_, context) = setup();
+        let blob = random_blob();
+        let (cells, proofs) = context.compute_cells_and_kzg_proofs(&blob).unwrap();
+
+        let cell_indices: Vec<_> = (0..cells.len()).filter(|cell_index| cell_index % 4 == 1 || cell_index % 4 == 2).collect();
+        let received: Vec<_> = cell_indices.iter().map(|cell_index| cells[*cell_index].clone()).collect();
+        let (recovered_cells, recovered_proofs) = context.recover_cells_and_kzg_proofs(&cell_indices, &received).unwrap();
+        assert_eq!(recovered_cells, cells);
+        assert_eq!(recovered_proofs, proofs);
+
+        assert_eq!(
+            context.recover_cells_and_kzg_proofs(&cell_indices[1..], &received[1..]),
+            Err(CellError::NotEnoughCells { received: 7, required: 8 })
+        );
+
+        let mut corrupted = received.clone();
+        corrupted[2][0] += blstrs::Scalar::one();
+        let mut cell_indices_with_extra = cell_indices.clone();
+        cell_indices_with_extra.push(0);
+        corrupted.push(cells[0].clone());
+        assert_eq!(
+            context.recover_cells_and_kzg_proofs(&cell_indices_with_extra, &corrupted),
+            Err(CellError::InconsistentCells)
+        );
+    }
+}
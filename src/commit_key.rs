@@ -1,4 +1,15 @@
-use crate::{domain::Domain, polynomial::Polynomial};
+use alloc::{sync::Arc, vec, vec::Vec};
+
+use group::{prime::PrimeCurveAffine, Curve};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{domain::Domain, fixed_base::FixedBaseTable, msm, polynomial::{Polynomial, SparsePolynomial}};
+
+pub use crate::msm::g1_lincomb;
+
+// A commitment to a polynomial
+pub type Commitment = blstrs::G1Affine;
 
 // The key that is used to commit to polynomials in monomial form
 //
@@ -6,6 +17,7 @@ use crate::{domain::Domain, polynomial::Polynomial};
 ///  Where:
 /// - `i` ranges from 0 to `degree`.
 /// - `G` is some generator of the group
+#[derive(Debug, Clone)]
 pub struct CommitKey { inner: Vec<blstrs::G1Affine>, }
 
 impl CommitKey {
@@ -17,9 +29,27 @@ impl CommitKey {
         CommitKey { inner: points }
     }
 
+    /// Returns the monomial points in this key
+    pub fn points(&self) -> &[blstrs::G1Affine] {
+        &self.inner
+    }
+
     // Note: There is no commit method for CommitKey in monomial basis as this is not used
     pub fn into_lagrange(self, domain: &Domain) -> CommitKeyLagrange {
-        CommitKeyLagrange { inner: domain.ifft_g1(self.inner) }
+        CommitKeyLagrange::new(domain.ifft_g1(self.inner))
+    }
+
+    /// Derives the lagrange key for `domain` from the first `domain.size()` points of this key
+    ///
+    /// Panics, if the domain is larger than the number of points in this key
+    pub fn to_lagrange(&self, domain: &Domain) -> CommitKeyLagrange {
+        assert!(
+            domain.size() <= self.inner.len(),
+            "domain size {} exceeds the number of points in the commit key {}",
+            domain.size(),
+            self.inner.len()
+        );
+        CommitKeyLagrange::new(domain.ifft_g1(self.inner[..domain.size()].to_vec()))
     }
 }
 
@@ -30,17 +60,92 @@ impl CommitKey {
 /// - `i` ranges from 0 to `degree`
 /// -  L_i is the i'th lagrange polynomial
 /// - `G` is some generator of the group
-pub struct CommitKeyLagrange { inner: Vec<blstrs::G1Affine>, }
+#[derive(Debug, Clone)]
+pub struct CommitKeyLagrange {
+    inner: Vec<blstrs::G1Affine>,
+    // Optional precomputed multiples of `inner`, see `precompute`
+    table: Option<Arc<FixedBaseTable>>,
+}
 
 impl CommitKeyLagrange {
     pub fn new(points: Vec<blstrs::G1Affine>) -> CommitKeyLagrange {
         assert!(points.len() > 1);
-        CommitKeyLagrange { inner: points }
+        CommitKeyLagrange { inner: points, table: None }
+    }
+
+    /// Precomputes multiples of the lagrange points, so that commitments only need
+    /// table lookups and additions
+    ///
+    /// The table takes `FixedBaseTable::estimate_memory_footprint(num_points, window_bits)` bytes.
+    /// Larger windows use less memory and fewer lookups, but have more buckets to sum up at the end.
+    /// For 4096 points, a window of 12 bits needs ~8.6MB, see `benches/msm.rs` for timings.
+    pub fn precompute(&mut self, window_bits: usize) {
+        self.table = Some(Arc::new(FixedBaseTable::new(&self.inner, window_bits)));
+    }
+
+    /// Returns the number of bytes used by the precomputed table, zero if there is none
+    pub fn precomputed_memory_footprint(&self) -> usize {
+        self.table.as_ref().map_or(0, |table| table.memory_footprint())
     }
 
     /// Commit to `polynomial` in lagrange form
     pub fn commit(&self, polynomial: &Polynomial) -> blstrs::G1Affine {
-        g1_lincomb(&self.inner, &polynomial.evaluations)
+        match &self.table {
+            Some(table) => {
+                assert_eq!(self.inner.len(), polynomial.evaluations.len());
+                table.msm(&polynomial.evaluations).into()
+            }
+            None => g1_lincomb(&self.inner, &polynomial.evaluations),
+        }
+    }
+
+    /// Commit to `polynomial` in lagrange form, only touching its non-zero evaluations
+    ///
+    /// The MSM only processes as many windows as the largest evaluation needs,
+    /// so polynomials whose evaluations are small (e.g. bytes) are especially cheap.
+    pub fn commit_sparse(&self, polynomial: &SparsePolynomial) -> Commitment {
+        assert_eq!(self.inner.len(), polynomial.size());
+
+        match &self.table {
+            Some(table) => table.msm_sparse(polynomial.iter()).into(),
+            None => {
+                let (points, scalars): (Vec<_>, Vec<_>) =
+                    polynomial.iter().map(|(index, evaluation)| (self.inner[index], *evaluation)).unzip();
+                msm::msm(&points, &scalars).into()
+            }
+        }
+    }
+
+    /// Commit to each of `polynomials` in lagrange form
+    ///
+    /// This is cheaper than calling `commit` in a loop: the points are copied
+    /// into blst's form once for all of the MSMs, and all of the commitments
+    /// are converted back to affine form with a single batch inversion.
+    /// With the `parallel` feature and a precomputed table, the polynomials are committed
+    /// to in parallel. Without a table, each MSM already runs on blst's thread pool.
+    pub fn commit_many(&self, polynomials: &[Polynomial]) -> Vec<Commitment> {
+        for polynomial in polynomials {
+            assert_eq!(self.inner.len(), polynomial.evaluations.len());
+        }
+
+        let commitments: Vec<_> = match &self.table {
+            Some(table) => map_polynomials(polynomials, |polynomial| table.msm(&polynomial.evaluations)),
+            None => {
+                let points = msm::to_blst_affines(&self.inner);
+                // blst already spreads each MSM over its own thread pool, running them
+                // on rayon's pool as well would oversubscribe the cores
+                polynomials.iter().map(|polynomial| msm::blst_msm(&points, &polynomial.evaluations)).collect()
+            }
+        };
+
+        let mut commitments_affine = vec![blstrs::G1Affine::identity(); commitments.len()];
+        blstrs::G1Projective::batch_normalize(&commitments, &mut commitments_affine);
+        commitments_affine
+    }
+
+    /// Returns the lagrange points in this key
+    pub fn points(&self) -> &[blstrs::G1Affine] {
+        &self.inner
     }
 
     /// Returns the maximum degree polynomial that one can commit to
@@ -53,22 +158,15 @@ impl CommitKeyLagrange {
     }
 }
 
-// A multi-scalar multiplication
-pub fn g1_lincomb(points: &[blstrs::G1Affine], scalars: &[blstrs::Scalar]) -> blstrs::G1Affine {
-    // TODO: We could use arkworks here and use their parallelized multi-exp instead
-
-    // TODO: Spec says we should panic, but as a lib its better to return result
-    assert_eq!(points.len(), scalars.len());
-
-    let points_iter = points.into_iter();
-
-    let points: Vec<_> = points_iter
-        .map(|point| blstrs::G1Projective::from(point))
-        .collect();
-
-    // blst does not use multiple threads
-    // TODO: the internal lib seems to be converting back to Affine
-    blstrs::G1Projective::multi_exp(&points, scalars).into()
+fn map_polynomials<F>(polynomials: &[Polynomial], f: F) -> Vec<blstrs::G1Projective>
+where
+    F: Fn(&Polynomial) -> blstrs::G1Projective + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    let commitments = polynomials.par_iter().map(f).collect();
+    #[cfg(not(feature = "parallel"))]
+    let commitments = polynomials.iter().map(f).collect();
+    commitments
 }
 
 #[cfg(test)]
@@ -81,7 +179,7 @@ mod tests {
     fn eval_coeff_poly(poly: &[blstrs::Scalar], input_point: &blstrs::Scalar) -> blstrs::Scalar {
         let mut result = blstrs::Scalar::zero();
         for (index, coeff) in poly.iter().enumerate() {
-            result += input_point.pow_vartime(&[index as u64]) * coeff;
+            result += input_point.pow_vartime([index as u64]) * coeff;
         }
         result
     }
@@ -93,7 +191,7 @@ mod tests {
         let domain = Domain::new(degree);
 
         // f(x) -- These are the coefficients of the polynomial
-        let f_x_coeffs: Vec<_> = (0..degree as u64).into_iter().map(blstrs::Scalar::from).collect();
+        let f_x_coeffs: Vec<_> = (0..degree as u64).map(blstrs::Scalar::from).collect();
 
         // Evaluate f(x) over the domain -- To get the evaluation form of f(x)
         let f_x_evaluations: Vec<_> = domain
@@ -105,7 +203,7 @@ mod tests {
         let secret = blstrs::Scalar::from(1234567u64);
         let monomial_srs: Vec<blstrs::G1Affine> = (0..degree)
             .map(|index| {
-                let secret_exp = secret.pow_vartime(&[index as u64]);
+                let secret_exp = secret.pow_vartime([index as u64]);
                 (blstrs::G1Affine::generator() * secret_exp).into()
             })
             .collect();
@@ -120,4 +218,76 @@ mod tests {
 
         assert_eq!(expected_commitment, got_commitment)
     }
+
+    #[test]
+    fn commit_sparse_matches_commit() {
+        use crate::{params::PublicParameters, polynomial::SparsePolynomial};
+
+        let domain = Domain::new(64);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+
+        // Byte sized evaluations in the first few slots, the rest is zero
+        let mut sparse = SparsePolynomial::new(64);
+        for (index, byte) in [0x01u64, 0xff, 0x7f, 0x80, 0x00, 0x10].into_iter().enumerate() {
+            sparse.insert(index, blstrs::Scalar::from(byte));
+        }
+        sparse.insert(40, blstrs::Scalar::random(&mut rand::thread_rng()));
+        assert_eq!(sparse.num_non_zero(), 6);
+
+        let mut commit_key = public_parameters.commit_key.clone();
+        let expected = commit_key.commit(&sparse.to_dense());
+        assert_eq!(commit_key.commit_sparse(&sparse), expected);
+
+        commit_key.precompute(4);
+        assert_eq!(commit_key.commit_sparse(&sparse), expected);
+        assert_eq!(
+            commit_key.commit_sparse(&SparsePolynomial::new(64)),
+            blstrs::G1Affine::identity()
+        );
+    }
+
+    #[test]
+    fn commit_many_matches_commit() {
+        use crate::{params::PublicParameters, polynomial::Polynomial};
+
+        let domain = Domain::new(16);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+        let polys: Vec<_> = (0..5)
+            .map(|_| Polynomial::new((0..16).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect()))
+            .collect();
+
+        let mut commit_key = public_parameters.commit_key.clone();
+        let expected: Vec<_> = polys.iter().map(|poly| commit_key.commit(poly)).collect();
+        assert_eq!(commit_key.commit_many(&polys), expected);
+
+        commit_key.precompute(4);
+        assert_eq!(commit_key.commit_many(&polys), expected);
+        assert!(commit_key.commit_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn precomputed_commit_matches() {
+        use crate::{params::PublicParameters, polynomial::Polynomial, proof::Proof};
+
+        let domain = Domain::new(32);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+        let poly = Polynomial::new((0..32).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect());
+
+        let mut commit_key = public_parameters.commit_key.clone();
+        assert_eq!(commit_key.precomputed_memory_footprint(), 0);
+        commit_key.precompute(6);
+        assert_eq!(
+            commit_key.precomputed_memory_footprint(),
+            FixedBaseTable::estimate_memory_footprint(32, 6)
+        );
+
+        let poly_comm = commit_key.commit(&poly);
+        assert_eq!(poly_comm, public_parameters.commit_key.commit(&poly));
+
+        let input_point = blstrs::Scalar::from(123456u64);
+        let proof = Proof::create(&commit_key, &poly, poly_comm, input_point, &domain);
+        let expected_proof = Proof::create(&public_parameters.commit_key, &poly, poly_comm, input_point, &domain);
+        assert_eq!(proof.quotient_commitment, expected_proof.quotient_commitment);
+        assert!(proof.verify(input_point, &public_parameters.opening_key));
+    }
 }
@@ -0,0 +1,234 @@
+use crate::{
+    commit_key::CommitKeyLagrange,
+    domain::Domain,
+    opening_key::OpeningKey,
+    params::PublicParameters,
+    polynomial::Polynomial,
+    proof::Proof,
+    verifier::{compute_challenge, verify_blob_kzg_proof, verify_blob_kzg_proof_batch},
+};
+
+/// Errors that can occur when the sizes of the domain, keys and polynomials do not line up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KzgContextError {
+    /// The commit key does not have one point per element in the domain
+    CommitKeySizeMismatch { domain_size: usize, commit_key_size: usize },
+    /// The SRS has fewer points than the domain has elements
+    DomainTooLarge { domain_size: usize, srs_size: usize },
+    /// The polynomial does not have one evaluation per element in the domain
+    PolynomialSizeMismatch { domain_size: usize, polynomial_size: usize },
+}
+
+impl core::fmt::Display for KzgContextError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            KzgContextError::CommitKeySizeMismatch { domain_size, commit_key_size } => write!(
+                f,
+                "commit key has {commit_key_size} points, but the domain has {domain_size} elements"
+            ),
+            KzgContextError::DomainTooLarge { domain_size, srs_size } => write!(
+                f,
+                "domain has {domain_size} elements, but the SRS only has {srs_size} points"
+            ),
+            KzgContextError::PolynomialSizeMismatch { domain_size, polynomial_size } => write!(
+                f,
+                "polynomial has {polynomial_size} evaluations, but the domain has {domain_size} elements"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for KzgContextError {}
+
+/// Bundles a `Domain` with the keys that were created for it
+///
+/// The sizes are checked once when the context is created, so the
+/// commit/prove/verify methods cannot be called with mismatched pieces.
+#[derive(Debug, Clone)]
+pub struct KzgContext {
+    domain: Domain,
+    commit_key: CommitKeyLagrange,
+    opening_key: OpeningKey,
+}
+
+impl KzgContext {
+    pub fn new(domain: Domain, commit_key: CommitKeyLagrange, opening_key: OpeningKey) -> Result<Self, KzgContextError> {
+        let commit_key_size = commit_key.points().len();
+        if commit_key_size != domain.size() {
+            return Err(KzgContextError::CommitKeySizeMismatch { domain_size: domain.size(), commit_key_size });
+        }
+
+        Ok(KzgContext { domain, commit_key, opening_key })
+    }
+
+    /// Creates a context for `domain`, deriving the commit key from the public parameters
+    pub fn from_public_parameters(public_parameters: &PublicParameters, domain: Domain) -> Result<Self, KzgContextError> {
+        let srs_size = public_parameters.commit_key_monomial.points().len();
+        if domain.size() > srs_size {
+            return Err(KzgContextError::DomainTooLarge { domain_size: domain.size(), srs_size });
+        }
+
+        let commit_key = public_parameters.commit_key_for_domain(&domain);
+        KzgContext::new(domain, CommitKeyLagrange::clone(&commit_key), public_parameters.opening_key.clone())
+    }
+
+    pub fn domain(&self) -> &Domain {
+        &self.domain
+    }
+
+    pub fn commit_key(&self) -> &CommitKeyLagrange {
+        &self.commit_key
+    }
+
+    pub fn opening_key(&self) -> &OpeningKey {
+        &self.opening_key
+    }
+
+    /// Commit to `polynomial` in lagrange form
+    pub fn commit(&self, polynomial: &Polynomial) -> Result<blstrs::G1Affine, KzgContextError> {
+        self.check_polynomial(polynomial)?;
+        Ok(self.commit_key.commit(polynomial))
+    }
+
+    /// Creates a proof that `polynomial` evaluates to `p(input_point)`
+    ///
+    /// `poly_comm` is the commitment to `polynomial`, it is not recomputed.
+    pub fn prove(
+        &self,
+        polynomial: &Polynomial,
+        poly_comm: blstrs::G1Affine,
+        input_point: blstrs::Scalar,
+    ) -> Result<Proof, KzgContextError> {
+        self.check_polynomial(polynomial)?;
+        Ok(Proof::create(&self.commit_key, polynomial, poly_comm, input_point, &self.domain))
+    }
+
+    pub fn verify(&self, proof: &Proof, input_point: blstrs::Scalar) -> bool {
+        proof.verify(input_point, &self.opening_key)
+    }
+
+    /// Creates the proof for a blob and its commitment that the consensus specs gossip, an opening
+    /// at a Fiat-Shamir challenge derived from both
+    pub fn compute_blob_kzg_proof(
+        &self,
+        blob: &Polynomial,
+        commitment: blstrs::G1Affine,
+    ) -> Result<blstrs::G1Affine, KzgContextError> {
+        let challenge = compute_challenge(blob, &commitment);
+        Ok(self.prove(blob, commitment, challenge)?.quotient_commitment)
+    }
+
+    /// Verifies a proof from `compute_blob_kzg_proof`
+    pub fn verify_blob_kzg_proof(
+        &self,
+        blob: &Polynomial,
+        commitment: blstrs::G1Affine,
+        proof: blstrs::G1Affine,
+    ) -> Result<bool, KzgContextError> {
+        self.check_polynomial(blob)?;
+        Ok(verify_blob_kzg_proof(&self.domain, &self.opening_key, blob, commitment, proof))
+    }
+
+    /// Verifies many proofs from `compute_blob_kzg_proof` at once
+    ///
+    /// The proofs are combined with a challenge derived from all of the inputs, as in the consensus specs,
+    /// so this does not need a source of randomness. It only tells whether all of the proofs are valid.
+    ///
+    /// Panics, if the inputs do not have the same length
+    pub fn verify_blob_kzg_proof_batch(
+        &self,
+        blobs: &[Polynomial],
+        commitments: &[blstrs::G1Affine],
+        proofs: &[blstrs::G1Affine],
+    ) -> Result<bool, KzgContextError> {
+        assert!(
+            blobs.len() == commitments.len() && blobs.len() == proofs.len(),
+            "blobs, commitments and proofs must have the same length"
+        );
+        for blob in blobs {
+            self.check_polynomial(blob)?;
+        }
+        Ok(verify_blob_kzg_proof_batch(&self.domain, &self.opening_key, blobs, commitments, proofs))
+    }
+
+    fn check_polynomial(&self, polynomial: &Polynomial) -> Result<(), KzgContextError> {
+        let polynomial_size = polynomial.evaluations.len();
+        if polynomial_size != self.domain.size() {
+            return Err(KzgContextError::PolynomialSizeMismatch { domain_size: self.domain.size(), polynomial_size });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+
+    use super::*;
+
+    fn random_polynomial(size: usize) -> Polynomial {
+        Polynomial::new((0..size).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect())
+    }
+
+    #[test]
+    fn mismatched_sizes_are_rejected() {
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &Domain::new(16));
+
+        let result = KzgContext::new(
+            Domain::new(8),
+            public_parameters.commit_key.clone(),
+            public_parameters.opening_key.clone(),
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            KzgContextError::CommitKeySizeMismatch { domain_size: 8, commit_key_size: 16 }
+        );
+
+        let result = KzgContext::from_public_parameters(&public_parameters, Domain::new(32));
+        assert_eq!(result.unwrap_err(), KzgContextError::DomainTooLarge { domain_size: 32, srs_size: 16 });
+
+        let context = KzgContext::from_public_parameters(&public_parameters, Domain::new(8)).unwrap();
+        assert_eq!(
+            context.commit(&random_polynomial(16)).unwrap_err(),
+            KzgContextError::PolynomialSizeMismatch { domain_size: 8, polynomial_size: 16 }
+        );
+    }
+
+    #[test]
+    fn commit_prove_verify() {
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &Domain::new(16));
+        let context = KzgContext::from_public_parameters(&public_parameters, Domain::new(8)).unwrap();
+
+        let poly = random_polynomial(8);
+        let poly_comm = context.commit(&poly).unwrap();
+
+        // A point outside of the domain and a point inside of the domain
+        for input_point in [blstrs::Scalar::from(123456u64), context.domain().roots()[3]] {
+            let proof = context.prove(&poly, poly_comm, input_point).unwrap();
+            assert!(context.verify(&proof, input_point));
+            assert!(!context.verify(&proof, input_point.double()));
+        }
+    }
+
+    #[test]
+    fn blob_proofs() {
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &Domain::new(16));
+        let context = KzgContext::from_public_parameters(&public_parameters, Domain::new(16)).unwrap();
+
+        let blob = random_polynomial(16);
+        let commitment = context.commit(&blob).unwrap();
+        let proof = context.compute_blob_kzg_proof(&blob, commitment).unwrap();
+        assert!(context.verify_blob_kzg_proof(&blob, commitment, proof).unwrap());
+
+        let other_blob = random_polynomial(16);
+        let other_commitment = context.commit(&other_blob).unwrap();
+        assert!(!context.verify_blob_kzg_proof(&other_blob, other_commitment, proof).unwrap());
+        assert!(!context.verify_blob_kzg_proof(&blob, other_commitment, proof).unwrap());
+
+        let other_proof = context.compute_blob_kzg_proof(&other_blob, other_commitment).unwrap();
+        let blobs = [blob, other_blob];
+        assert!(context.verify_blob_kzg_proof_batch(&blobs, &[commitment, other_commitment], &[proof, other_proof]).unwrap());
+        assert!(!context.verify_blob_kzg_proof_batch(&blobs, &[commitment, other_commitment], &[other_proof, proof]).unwrap());
+        assert!(context.verify_blob_kzg_proof_batch(&[], &[], &[]).unwrap());
+    }
+}
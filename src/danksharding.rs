@@ -0,0 +1,411 @@
+use alloc::{sync::Arc, vec, vec::Vec};
+
+use ff::Field;
+use group::prime::PrimeCurveAffine;
+use rand_core::RngCore;
+
+use crate::{
+    cells::{recover_coefficients, vanishing_polynomial_of_cosets, Cell, CellContext, CellError, CellProof},
+    commit_key::Commitment,
+    domain::Domain,
+    params::PublicParameters,
+    polynomial::Polynomial,
+    utils::{reverse_bit_order, reverse_bits},
+};
+
+/// Errors that can occur when extending, sampling or reconstructing the 2D data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DankshardingError {
+    /// The number of rows must be a power of two
+    InvalidRowCount { num_rows: usize },
+    /// The number of blobs or commitments is not the number of rows
+    RowCountMismatch { num_rows: usize, received: usize },
+    /// The row index is not smaller than the number of rows in the extended data
+    InvalidRowIndex { row: usize, num_rows: usize },
+    /// The same row was passed more than once
+    DuplicateRowIndex { row: usize },
+    /// At least half of the rows are needed to reconstruct a column
+    NotEnoughRows { received: usize, required: usize },
+    /// The cells in a column are not an extension of the original rows
+    InconsistentColumn,
+    /// An error from the cells of a row
+    Cell(CellError),
+}
+
+impl core::fmt::Display for DankshardingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DankshardingError::InvalidRowCount { num_rows } => {
+                write!(f, "number of rows {num_rows} must be a power of two")
+            }
+            DankshardingError::RowCountMismatch { num_rows, received } => {
+                write!(f, "received {received} rows, but there are {num_rows} rows")
+            }
+            DankshardingError::InvalidRowIndex { row, num_rows } => {
+                write!(f, "row {row} is out of range, there are {num_rows} extended rows")
+            }
+            DankshardingError::DuplicateRowIndex { row } => write!(f, "row {row} was passed more than once"),
+            DankshardingError::NotEnoughRows { received, required } => {
+                write!(f, "received {received} rows, but at least {required} are needed for reconstruction")
+            }
+            DankshardingError::InconsistentColumn => write!(f, "cells are not an extension of the original rows"),
+            DankshardingError::Cell(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl core::error::Error for DankshardingError {}
+
+impl From<CellError> for DankshardingError {
+    fn from(error: CellError) -> Self {
+        DankshardingError::Cell(error)
+    }
+}
+
+/// A cell of the extended data, with its position and proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    pub row: usize,
+    pub column: usize,
+    pub cell: Cell,
+    pub proof: CellProof,
+}
+
+/// The 2D extension of `k` blobs, with `2k` rows of cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedBlobs {
+    /// The commitments to the `2k` rows
+    pub commitments: Vec<Commitment>,
+    /// The cells of each row, indexed by row and then by column
+    pub cells: Vec<Vec<Cell>>,
+    /// The proofs for the cells, indexed like `cells`
+    pub proofs: Vec<Vec<CellProof>>,
+}
+
+impl ExtendedBlobs {
+    /// Returns the sample at `(row, column)`
+    ///
+    /// Panics, if the position is out of range
+    pub fn sample(&self, row: usize, column: usize) -> Sample {
+        Sample { row, column, cell: self.cells[row][column].clone(), proof: self.proofs[row][column] }
+    }
+}
+
+/// 2D danksharding over `k` blobs
+///
+/// Each blob is a row, which is extended to twice its size and split into cells as in `CellContext`.
+/// The `k` rows are then extended to `2k` rows with a Reed-Solomon code over each column: the row
+/// polynomials are taken as the evaluations of a polynomial of degree less than `k` over the domain of
+/// size `k`, and the extended rows are its evaluations over the domain of size `2k`. As for the cells,
+/// the rows are in bit reversed order, so the first `k` extended rows are the original blobs.
+///
+/// Commitments and proofs are linear in the polynomials, so the commitments and proofs of the
+/// extended rows are computed with the same extension, using FFTs over G1.
+pub struct DankshardingContext {
+    cell_context: CellContext,
+    row_domain: Arc<Domain>,
+    extended_row_domain: Arc<Domain>,
+}
+
+impl DankshardingContext {
+    /// Creates the context for `num_rows` blobs of `blob_size` field elements, with cells of `cell_size`
+    /// field elements
+    pub fn new(
+        public_parameters: &PublicParameters,
+        blob_size: usize,
+        cell_size: usize,
+        num_rows: usize,
+    ) -> Result<Self, DankshardingError> {
+        if !num_rows.is_power_of_two() {
+            return Err(DankshardingError::InvalidRowCount { num_rows });
+        }
+
+        Ok(DankshardingContext {
+            cell_context: CellContext::new(public_parameters, blob_size, cell_size)?,
+            row_domain: Domain::get(num_rows),
+            extended_row_domain: Domain::get(2 * num_rows),
+        })
+    }
+
+    pub fn cell_context(&self) -> &CellContext {
+        &self.cell_context
+    }
+
+    /// The number of blobs, before the extension
+    pub fn num_rows(&self) -> usize {
+        self.row_domain.size()
+    }
+
+    /// The number of rows after the extension
+    pub fn num_extended_rows(&self) -> usize {
+        self.extended_row_domain.size()
+    }
+
+    /// Extends the commitments to the `k` blobs to the commitments to the `2k` rows
+    pub fn extend_commitments(&self, commitments: &[Commitment]) -> Result<Vec<Commitment>, DankshardingError> {
+        self.check_num_rows(commitments.len())?;
+        Ok(self.extend_points(commitments.to_vec()))
+    }
+
+    /// Checks that the second half of the `2k` commitments is the extension of the first half
+    pub fn verify_extended_commitments(&self, extended_commitments: &[Commitment]) -> bool {
+        if extended_commitments.len() != self.num_extended_rows() {
+            return false;
+        }
+        // The extension has degree less than `k`, so the upper coefficients are zero
+        let mut points = extended_commitments.to_vec();
+        reverse_bit_order(&mut points);
+        let coefficients = self.extended_row_domain.ifft_g1(points);
+        coefficients[self.num_rows()..].iter().all(|point| bool::from(point.is_identity()))
+    }
+
+    /// Extends the blobs to `2k` rows, and computes the cells and proofs of every row
+    ///
+    /// The cell proofs are only computed for the original rows, the proofs of the extended rows
+    /// are the extension of the proofs in each column.
+    pub fn extend_blobs(
+        &self,
+        blobs: &[Polynomial],
+        commitments: &[Commitment],
+    ) -> Result<ExtendedBlobs, DankshardingError> {
+        self.check_num_rows(blobs.len())?;
+        self.check_num_rows(commitments.len())?;
+
+        let mut rows_cells = Vec::with_capacity(blobs.len());
+        let mut rows_proofs = Vec::with_capacity(blobs.len());
+        for blob in blobs {
+            let (cells, proofs) = self.cell_context.compute_cells_and_kzg_proofs(blob)?;
+            rows_cells.push(cells);
+            rows_proofs.push(proofs);
+        }
+
+        let num_cells = self.cell_context.num_cells();
+        let cell_size = self.cell_context.cell_size();
+        let num_extended_rows = self.num_extended_rows();
+        let mut cells = vec![vec![vec![blstrs::Scalar::zero(); cell_size]; num_cells]; num_extended_rows];
+        let mut proofs = vec![Vec::with_capacity(num_cells); num_extended_rows];
+        for column in 0..num_cells {
+            for position in 0..cell_size {
+                let values = rows_cells.iter().map(|row| row[column][position]).collect();
+                for (row, value) in self.extend_scalars(values).into_iter().enumerate() {
+                    cells[row][column][position] = value;
+                }
+            }
+
+            let column_proofs = rows_proofs.iter().map(|row| row[column]).collect();
+            for (row, proof) in self.extend_points(column_proofs).into_iter().enumerate() {
+                proofs[row].push(proof);
+            }
+        }
+
+        Ok(ExtendedBlobs { commitments: self.extend_points(commitments.to_vec()), cells, proofs })
+    }
+
+    /// Verifies samples from the extended data against the `2k` extended commitments
+    ///
+    /// Note: This is a probabilistic check, `rng` must be a cryptographically secure rng.
+    pub fn verify_samples(
+        &self,
+        extended_commitments: &[Commitment],
+        samples: &[Sample],
+        rng: impl RngCore,
+    ) -> Result<bool, DankshardingError> {
+        if extended_commitments.len() != self.num_extended_rows() {
+            return Err(DankshardingError::RowCountMismatch {
+                num_rows: self.num_extended_rows(),
+                received: extended_commitments.len(),
+            });
+        }
+        for sample in samples {
+            self.check_row(sample.row)?;
+        }
+
+        let commitments: Vec<_> = samples.iter().map(|sample| extended_commitments[sample.row]).collect();
+        let cell_indices: Vec<_> = samples.iter().map(|sample| sample.column).collect();
+        let cells: Vec<_> = samples.iter().map(|sample| sample.cell.clone()).collect();
+        let proofs: Vec<_> = samples.iter().map(|sample| sample.proof).collect();
+        Ok(self.cell_context.verify_cell_kzg_proof_batch(&commitments, &cell_indices, &cells, &proofs, rng)?)
+    }
+
+    /// Reconstructs all of the cells and proofs of a row from at least half of its cells
+    pub fn reconstruct_row(
+        &self,
+        columns: &[usize],
+        cells: &[Cell],
+    ) -> Result<(Vec<Cell>, Vec<CellProof>), DankshardingError> {
+        Ok(self.cell_context.recover_cells_and_kzg_proofs(columns, cells)?)
+    }
+
+    /// Reconstructs all `2k` cells of a column from the cells of at least half of the rows
+    ///
+    /// Every position in the cells is a Reed-Solomon codeword over the rows, which is recovered
+    /// in the same way as a blob is recovered from its cells.
+    pub fn reconstruct_column(&self, rows: &[usize], cells: &[Cell]) -> Result<Vec<Cell>, DankshardingError> {
+        if rows.len() != cells.len() {
+            return Err(CellError::LengthMismatch.into());
+        }
+        let cell_size = self.cell_context.cell_size();
+        let num_extended_rows = self.num_extended_rows();
+        let mut received = vec![false; num_extended_rows];
+        for (row, cell) in rows.iter().zip(cells) {
+            self.check_row(*row)?;
+            if cell.len() != cell_size {
+                return Err(CellError::CellSizeMismatch { cell_size, received: cell.len() }.into());
+            }
+            if core::mem::replace(&mut received[*row], true) {
+                return Err(DankshardingError::DuplicateRowIndex { row: *row });
+            }
+        }
+        let required = self.num_rows();
+        if rows.len() < required {
+            return Err(DankshardingError::NotEnoughRows { received: rows.len(), required });
+        }
+
+        // Row `i` is the evaluation at the `brp(i)`-th root of unity
+        let missing_roots = (0..num_extended_rows)
+            .filter(|row| !received[*row])
+            .map(|row| self.extended_row_domain.roots[reverse_bits(row, num_extended_rows)]);
+        let vanishing_polynomial = vanishing_polynomial_of_cosets(missing_roots, 1, num_extended_rows);
+
+        let mut column = vec![vec![blstrs::Scalar::zero(); cell_size]; num_extended_rows];
+        for position in 0..cell_size {
+            let mut evaluations = vec![blstrs::Scalar::zero(); num_extended_rows];
+            for (row, cell) in rows.iter().zip(cells) {
+                evaluations[*row] = cell[position];
+            }
+            reverse_bit_order(&mut evaluations);
+
+            let coefficients = recover_coefficients(
+                &self.extended_row_domain,
+                evaluations,
+                vanishing_polynomial.clone(),
+                self.num_rows(),
+            )
+            .ok_or(DankshardingError::InconsistentColumn)?;
+
+            for (row, value) in self.extend_coefficients(coefficients).into_iter().enumerate() {
+                column[row][position] = value;
+            }
+        }
+        Ok(column)
+    }
+
+    fn check_num_rows(&self, received: usize) -> Result<(), DankshardingError> {
+        if received != self.num_rows() {
+            return Err(DankshardingError::RowCountMismatch { num_rows: self.num_rows(), received });
+        }
+        Ok(())
+    }
+
+    fn check_row(&self, row: usize) -> Result<(), DankshardingError> {
+        if row >= self.num_extended_rows() {
+            return Err(DankshardingError::InvalidRowIndex { row, num_rows: self.num_extended_rows() });
+        }
+        Ok(())
+    }
+
+    // Extends the values of the `k` rows in bit reversed order to the `2k` rows
+    fn extend_scalars(&self, mut values: Vec<blstrs::Scalar>) -> Vec<blstrs::Scalar> {
+        reverse_bit_order(&mut values);
+        let coefficients = self.row_domain.ifft_scalars(values);
+        self.extend_coefficients(coefficients)
+    }
+
+    fn extend_coefficients(&self, mut coefficients: Vec<blstrs::Scalar>) -> Vec<blstrs::Scalar> {
+        coefficients.resize(self.num_extended_rows(), blstrs::Scalar::zero());
+        let mut extended = self.extended_row_domain.fft_scalars(coefficients);
+        reverse_bit_order(&mut extended);
+        extended
+    }
+
+    // The same as `extend_scalars`, for commitments and proofs
+    fn extend_points(&self, mut points: Vec<blstrs::G1Affine>) -> Vec<blstrs::G1Affine> {
+        reverse_bit_order(&mut points);
+        let mut coefficients = self.row_domain.ifft_g1(points);
+        coefficients.resize(self.num_extended_rows(), blstrs::G1Affine::identity());
+        let mut extended = self.extended_row_domain.fft_g1(coefficients);
+        reverse_bit_order(&mut extended);
+        extended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{danksharding_setup as setup, NUM_ROWS};
+
+    use super::*;
+
+    #[test]
+    fn extended_rows_are_blobs() {
+        let (public_parameters, context, blobs, commitments) = setup();
+        let extended = context.extend_blobs(&blobs, &commitments).unwrap();
+        assert_eq!(extended.commitments, context.extend_commitments(&commitments).unwrap());
+        assert_eq!(extended.commitments[..NUM_ROWS], commitments);
+        assert!(context.verify_extended_commitments(&extended.commitments));
+
+        for row in 0..context.num_extended_rows() {
+            // The first half of the cells of a row is its blob, in bit reversed order
+            let mut evaluations = extended.cells[row][..extended.cells[row].len() / 2].concat();
+            reverse_bit_order(&mut evaluations);
+            let blob = Polynomial::new(evaluations);
+
+            assert_eq!(public_parameters.commit_key.commit(&blob), extended.commitments[row]);
+            let (cells, proofs) = context.cell_context().compute_cells_and_kzg_proofs(&blob).unwrap();
+            assert_eq!(cells, extended.cells[row]);
+            assert_eq!(proofs, extended.proofs[row]);
+        }
+
+        let mut tampered = extended.commitments.clone();
+        tampered.swap(NUM_ROWS, NUM_ROWS + 1);
+        assert!(!context.verify_extended_commitments(&tampered));
+    }
+
+    #[test]
+    fn verify_samples_from_extended_rows() {
+        let (_, context, blobs, commitments) = setup();
+        let extended = context.extend_blobs(&blobs, &commitments).unwrap();
+
+        let samples: Vec<_> = [(0, 0), (2, 5), (5, 1), (7, 7)]
+            .into_iter()
+            .map(|(row, column)| extended.sample(row, column))
+            .collect();
+        assert!(context.verify_samples(&extended.commitments, &samples, rand::thread_rng()).unwrap());
+
+        let mut moved = samples.clone();
+        moved[2].row = 6;
+        assert!(!context.verify_samples(&extended.commitments, &moved, rand::thread_rng()).unwrap());
+
+        moved[2].row = 8;
+        assert_eq!(
+            context.verify_samples(&extended.commitments, &moved, rand::thread_rng()),
+            Err(DankshardingError::InvalidRowIndex { row: 8, num_rows: 8 })
+        );
+    }
+
+    #[test]
+    fn reconstruct_rows_and_columns() {
+        let (_, context, blobs, commitments) = setup();
+        let extended = context.extend_blobs(&blobs, &commitments).unwrap();
+
+        // A column from half of the rows, including extended rows
+        let column = 3;
+        let rows = [1, 4, 6, 7];
+        let cells: Vec<_> = rows.iter().map(|row| extended.cells[*row][column].clone()).collect();
+        let reconstructed = context.reconstruct_column(&rows, &cells).unwrap();
+        let expected: Vec<_> = extended.cells.iter().map(|row| row[column].clone()).collect();
+        assert_eq!(reconstructed, expected);
+
+        assert_eq!(
+            context.reconstruct_column(&rows[1..], &cells[1..]),
+            Err(DankshardingError::NotEnoughRows { received: 3, required: 4 })
+        );
+
+        // An extended row from half of its cells
+        let row = 5;
+        let columns = [0, 2, 3, 6];
+        let cells: Vec<_> = columns.iter().map(|column| extended.cells[row][*column].clone()).collect();
+        let (row_cells, row_proofs) = context.reconstruct_row(&columns, &cells).unwrap();
+        assert_eq!(row_cells, extended.cells[row]);
+        assert_eq!(row_proofs, extended.proofs[row]);
+    }
+}
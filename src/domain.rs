@@ -1,11 +1,28 @@
 
-use group::{prime::PrimeCurveAffine, Curve, Group};
+use alloc::{sync::Arc, vec, vec::Vec};
+use core::ops::{Add, Mul, Sub};
+#[cfg(feature = "std")]
+use std::{collections::HashMap, sync::{OnceLock, RwLock}};
+
+use group::{prime::PrimeCurveAffine, Curve};
 use ff::{Field, PrimeField};
 
+use crate::utils;
+
+// Domains that have been created through `Domain::get`, indexed by size
+#[cfg(feature = "std")]
+static DOMAIN_REGISTRY: OnceLock<RwLock<HashMap<usize, Arc<Domain>>>> = OnceLock::new();
+
 #[derive(Debug, Clone)]
 pub struct Domain {
     // roots of unity
+    //
+    // These double as the twiddle factors for the FFT
     pub roots: Vec<blstrs::Scalar>,
+    // Inverses of the roots of unity
+    //
+    // These double as the twiddle factors for the inverse FFT
+    pub inverse_roots: Vec<blstrs::Scalar>,
     // Domain size as a scalar
     pub domain_size: blstrs::Scalar,
     // Inverse of the domain size as a scalar
@@ -43,8 +60,12 @@ impl Domain {
             roots.push(prev_root * generator)
         }
 
+        // Since w^size = 1, the inverse of w^i is w^(size - i)
+        let inverse_roots = (0..size).map(|i| roots[(size - i) % size]).collect();
+
         Self {
             roots,
+            inverse_roots,
             domain_size: size_as_scalar,
             domain_size_inv: size_as_scalar_inv,
             generator,
@@ -52,6 +73,35 @@ impl Domain {
         }
     }
 
+    /// Returns the domain of `size` from a global registry
+    ///
+    /// The domain is created on first use, subsequent calls for the same size
+    /// return the same precomputed domain. Like `Domain::new`, the size is padded
+    /// to the next power of two. Without the `std` feature there is no registry,
+    /// and every call creates a new domain.
+    pub fn get(size: usize) -> Arc<Domain> {
+        Self::registered(size.next_power_of_two())
+    }
+
+    #[cfg(feature = "std")]
+    fn registered(size: usize) -> Arc<Domain> {
+        let registry = DOMAIN_REGISTRY.get_or_init(|| RwLock::new(HashMap::new()));
+
+        if let Some(domain) = registry.read().unwrap().get(&size) {
+            return domain.clone();
+        }
+
+        // Another thread may have created the domain in the meantime, so we
+        // only insert if the entry is still empty
+        let mut registry = registry.write().unwrap();
+        registry.entry(size).or_insert_with(|| Arc::new(Domain::new(size))).clone()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn registered(size: usize) -> Arc<Domain> {
+        Arc::new(Domain::new(size))
+    }
+
     fn largest_root_of_unity() -> blstrs::Scalar {
         blstrs::Scalar::from_str_vartime(
             "10238227357739495823651030575849232062558860180284477541189508159991286009131",
@@ -70,7 +120,7 @@ impl Domain {
         // We now want to compute the generator which has order `size`
         let exponent: u64 = 1 << (Domain::two_adicity() as u64 - log_size_of_group as u64);
 
-        Domain::largest_root_of_unity().pow_vartime(&[exponent])
+        Domain::largest_root_of_unity().pow_vartime([exponent])
     }
 
     const fn two_adicity() -> u32 {
@@ -89,33 +139,73 @@ impl Domain {
         &self.roots
     }
 
-    pub fn ifft_g1(&self, points: Vec<blstrs::G1Affine>) -> Vec<blstrs::G1Affine> {
-        if points.len() != self.size() {
-            panic!(
-                "number of points {}, must equal the domain size {}",
-                points.len(),
-                self.size()
-            )
+    /// Evaluates the polynomial whose coefficients are `coefficients` over the domain
+    pub fn fft_scalars(&self, mut coefficients: Vec<blstrs::Scalar>) -> Vec<blstrs::Scalar> {
+        self.assert_size(coefficients.len());
+        fft_in_place(&mut coefficients, &self.roots);
+        coefficients
+    }
+
+    /// Interpolates `evaluations` over the domain, returning the coefficients of the polynomial
+    pub fn ifft_scalars(&self, mut evaluations: Vec<blstrs::Scalar>) -> Vec<blstrs::Scalar> {
+        self.assert_size(evaluations.len());
+        fft_in_place(&mut evaluations, &self.inverse_roots);
+        for element in evaluations.iter_mut() {
+            *element *= self.domain_size_inv
         }
+        evaluations
+    }
+
+    /// Evaluates the polynomial whose coefficients are `points` (in the exponent) over the domain
+    ///
+    /// This is the inverse of `ifft_g1`, it maps a lagrange SRS back to a monomial SRS.
+    pub fn fft_g1(&self, points: Vec<blstrs::G1Affine>) -> Vec<blstrs::G1Affine> {
+        self.assert_size(points.len());
+
+        let mut points_proj: Vec<_> = points
+            .into_iter()
+            .map(blstrs::G1Projective::from)
+            .collect();
+
+        fft_in_place(&mut points_proj, &self.roots);
+
+        let mut affine = vec![blstrs::G1Affine::identity(); points_proj.len()];
+        blstrs::G1Projective::batch_normalize(&points_proj, &mut affine);
+        affine
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn ifft_g1(&self, points: Vec<blstrs::G1Affine>) -> Vec<blstrs::G1Affine> {
+        self.assert_size(points.len());
 
-        let points_proj: Vec<_> = points
+        let mut ifft_g1: Vec<_> = points
             .into_iter()
-            .map(|point_aff| blstrs::G1Projective::from(point_aff))
+            .map(blstrs::G1Projective::from)
             .collect();
 
-        let mut ifft_g1 = fft_g1(self.generator_inv, &points_proj);
+        fft_in_place(&mut ifft_g1, &self.inverse_roots);
 
         for element in ifft_g1.iter_mut() {
-            *element = *element * self.domain_size_inv
+            *element *= self.domain_size_inv
         }
 
         let mut affine = vec![blstrs::G1Affine::identity(); ifft_g1.len()];
         blstrs::G1Projective::batch_normalize(&ifft_g1, &mut affine);
-        return affine;
+        affine
+    }
+
+    fn assert_size(&self, num_elements: usize) {
+        if num_elements != self.size() {
+            panic!(
+                "number of elements {}, must equal the domain size {}",
+                num_elements,
+                self.size()
+            )
+        }
     }
 }
 
-impl std::ops::Index<usize> for &Domain {
+impl core::ops::Index<usize> for &Domain {
     type Output = blstrs::Scalar;
 
     fn index(&self, i: usize) -> &Self::Output {
@@ -123,53 +213,74 @@ impl std::ops::Index<usize> for &Domain {
     }
 }
 
-fn fft_g1(nth_root_of_unity: blstrs::Scalar, points: &[blstrs::G1Projective]) -> Vec<blstrs::G1Projective> {
-    let n = points.len();
-    if n == 1 {
-        return points.to_vec();
+// An iterative radix-2 FFT
+//
+// `twiddles` are the powers of a root of unity whose order is the length of `values`.
+// The output is in natural order: values[k] = \sum_j values[j] * twiddles[1]^(j*k)
+fn fft_in_place<T>(values: &mut [T], twiddles: &[blstrs::Scalar])
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<blstrs::Scalar, Output = T>,
+{
+    let n = values.len();
+    assert_eq!(n, twiddles.len());
+
+    utils::reverse_bit_order(values);
+
+    let mut half_size = 1;
+    while half_size < n {
+        // The twiddles for this layer are the powers of a root of order `2 * half_size`
+        let stride = n / (2 * half_size);
+        for start in (0..n).step_by(2 * half_size) {
+            for j in 0..half_size {
+                let tmp = values[start + j + half_size] * twiddles[j * stride];
+                let even = values[start + j];
+                values[start + j] = even + tmp;
+                values[start + j + half_size] = even - tmp;
+            }
+        }
+        half_size *= 2;
     }
+}
 
-    let (even, odd) = take_even_odd(points);
-
-    // Compute a root with half the order
-    let gen_squared = nth_root_of_unity.square();
-
-    let fft_even = fft_g1(gen_squared, &even);
-    let fft_odd = fft_g1(gen_squared, &odd);
-
-    let mut input_point = blstrs::Scalar::one();
-    let mut evaluations = vec![blstrs::G1Projective::identity(); n];
-
-    for k in 0..n / 2 {
-        let tmp = fft_odd[k] * input_point;
-        evaluations[k] = blstrs::G1Projective::from(fft_even[k]) + tmp;
-        evaluations[k + n / 2] = fft_even[k] - tmp;
-
-        input_point = input_point * nth_root_of_unity;
-    }
+#[test]
+fn largest_group_has_correct_order() {
+    let root = Domain::largest_root_of_unity();
+    let order = 2u64.pow(Domain::two_adicity());
 
-    evaluations
+    assert_eq!(root.pow_vartime([order]), blstrs::Scalar::one())
 }
 
-fn take_even_odd<T: Clone>(list: &[T]) -> (Vec<T>, Vec<T>) {
-    let mut even = Vec::with_capacity(list.len() / 2);
-    let mut odd = Vec::with_capacity(list.len() / 2);
-
-    for (index, value) in list.iter().enumerate() {
-        if index % 2 == 0 {
-            even.push(value.clone())
-        } else {
-            odd.push(value.clone())
-        }
+#[test]
+fn fft_scalars_matches_naive_evaluation() {
+    let domain = Domain::new(16);
+    let coefficients: Vec<_> = (0..16u64).map(|i| blstrs::Scalar::from(i * i + 7)).collect();
+
+    let evaluations = domain.fft_scalars(coefficients.clone());
+    for (root, evaluation) in domain.roots().iter().zip(&evaluations) {
+        let expected: blstrs::Scalar = coefficients
+            .iter()
+            .enumerate()
+            .map(|(index, coeff)| root.pow_vartime([index as u64]) * coeff)
+            .sum();
+        assert_eq!(*evaluation, expected);
     }
 
-    (even, odd)
+    assert_eq!(domain.ifft_scalars(evaluations), coefficients);
 }
 
+#[cfg(feature = "std")]
 #[test]
-fn largest_group_has_correct_order() {
-    let root = Domain::largest_root_of_unity();
-    let order = 2u64.pow(Domain::two_adicity());
+fn registry_returns_shared_domains() {
+    let handles: Vec<_> = (0..4).map(|_| std::thread::spawn(|| Domain::get(64))).collect();
+    let domains: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
 
-    assert_eq!(root.pow_vartime(&[order]), blstrs::Scalar::one())
+    for domain in &domains {
+        assert!(Arc::ptr_eq(domain, &domains[0]));
+    }
+    assert!(Arc::ptr_eq(&Domain::get(33), &domains[0]));
+    assert_eq!(domains[0].roots(), Domain::new(64).roots());
+
+    for (root, inverse_root) in domains[0].roots().iter().zip(&domains[0].inverse_roots) {
+        assert_eq!(root * inverse_root, blstrs::Scalar::one());
+    }
 }
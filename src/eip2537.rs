@@ -0,0 +1,253 @@
+use group::prime::PrimeCurveAffine;
+
+use crate::{opening_key::OpeningKey, proof::Proof, serialization::PointDecodingError, SCALAR_SERIALIZED_SIZE};
+
+// The number of bytes in a big endian base field element
+const FP_SIZE: usize = 48;
+// The number of bytes in a base field element, padded with zeros at the front
+const PADDED_FP_SIZE: usize = 64;
+/// The number of bytes in an EIP-2537 encoded G1 point
+pub const G1_ENCODED_SIZE: usize = 2 * PADDED_FP_SIZE;
+/// The number of bytes in an EIP-2537 encoded G2 point
+pub const G2_ENCODED_SIZE: usize = 4 * PADDED_FP_SIZE;
+/// The number of bytes in one pair of the pairing precompile's input
+pub const PAIRING_PAIR_SIZE: usize = G1_ENCODED_SIZE + G2_ENCODED_SIZE;
+
+/// Encodes a G1 point for the BLS12-381 precompiles
+///
+/// The coordinates are padded to 64 bytes each, and the point at infinity is all zeros.
+pub fn encode_g1(point: &blstrs::G1Affine) -> [u8; G1_ENCODED_SIZE] {
+    let mut encoded = [0u8; G1_ENCODED_SIZE];
+    if bool::from(point.is_identity()) {
+        return encoded;
+    }
+
+    // blst writes x and y as big endian, and the flag bits are zero for a finite point
+    let uncompressed = point.to_uncompressed();
+    for (coordinate, padded) in uncompressed.chunks_exact(FP_SIZE).zip(encoded.chunks_exact_mut(PADDED_FP_SIZE)) {
+        padded[PADDED_FP_SIZE - FP_SIZE..].copy_from_slice(coordinate);
+    }
+    encoded
+}
+
+/// Encodes a G2 point for the BLS12-381 precompiles
+///
+/// Each coordinate `c0 + c1 * u` is encoded as `c0` followed by `c1`, which is the reverse of
+/// the order in the compressed and uncompressed encodings.
+pub fn encode_g2(point: &blstrs::G2Affine) -> [u8; G2_ENCODED_SIZE] {
+    let mut encoded = [0u8; G2_ENCODED_SIZE];
+    if bool::from(point.is_identity()) {
+        return encoded;
+    }
+
+    // blst writes x.c1, x.c0, y.c1, y.c0
+    let uncompressed = point.to_uncompressed();
+    for (index, component) in uncompressed.chunks_exact(FP_SIZE).enumerate() {
+        let swapped = index ^ 1;
+        encoded[swapped * PADDED_FP_SIZE + PADDED_FP_SIZE - FP_SIZE..][..FP_SIZE].copy_from_slice(component);
+    }
+    encoded
+}
+
+/// Decodes a G1 point in the precompiles' encoding, checking that it is in the subgroup
+pub fn decode_g1(encoded: &[u8; G1_ENCODED_SIZE]) -> Result<blstrs::G1Affine, PointDecodingError> {
+    if encoded.iter().all(|byte| *byte == 0) {
+        return Ok(blstrs::G1Affine::identity());
+    }
+
+    let mut uncompressed = [0u8; 2 * FP_SIZE];
+    for (padded, coordinate) in encoded.chunks_exact(PADDED_FP_SIZE).zip(uncompressed.chunks_exact_mut(FP_SIZE)) {
+        coordinate.copy_from_slice(unpad(padded)?);
+    }
+    check_flags(uncompressed[0])?;
+
+    let point: Option<blstrs::G1Affine> = blstrs::G1Affine::from_uncompressed_unchecked(&uncompressed).into();
+    let point = point.ok_or(PointDecodingError::InvalidEncoding)?;
+    if !bool::from(point.is_torsion_free()) {
+        return Err(PointDecodingError::NotInSubgroup);
+    }
+    Ok(point)
+}
+
+/// Decodes a G2 point in the precompiles' encoding, checking that it is in the subgroup
+pub fn decode_g2(encoded: &[u8; G2_ENCODED_SIZE]) -> Result<blstrs::G2Affine, PointDecodingError> {
+    if encoded.iter().all(|byte| *byte == 0) {
+        return Ok(blstrs::G2Affine::identity());
+    }
+
+    let mut uncompressed = [0u8; 4 * FP_SIZE];
+    for (index, padded) in encoded.chunks_exact(PADDED_FP_SIZE).enumerate() {
+        let swapped = index ^ 1;
+        uncompressed[swapped * FP_SIZE..][..FP_SIZE].copy_from_slice(unpad(padded)?);
+    }
+    check_flags(uncompressed[0])?;
+
+    let point: Option<blstrs::G2Affine> = blstrs::G2Affine::from_uncompressed_unchecked(&uncompressed).into();
+    let point = point.ok_or(PointDecodingError::InvalidEncoding)?;
+    if !bool::from(point.is_torsion_free()) {
+        return Err(PointDecodingError::NotInSubgroup);
+    }
+    Ok(point)
+}
+
+/// Encodes a scalar as the 32 byte big endian integer that the MSM precompiles take
+pub fn encode_scalar(scalar: &blstrs::Scalar) -> [u8; SCALAR_SERIALIZED_SIZE] {
+    scalar.to_bytes_be()
+}
+
+/// A `Proof` in the precompiles' encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodedProof {
+    pub polynomial_commitment: [u8; G1_ENCODED_SIZE],
+    pub quotient_commitment: [u8; G1_ENCODED_SIZE],
+    pub output_point: [u8; SCALAR_SERIALIZED_SIZE],
+}
+
+impl From<&Proof> for EncodedProof {
+    fn from(proof: &Proof) -> Self {
+        EncodedProof {
+            polynomial_commitment: encode_g1(&proof.polynomial_commitment),
+            quotient_commitment: encode_g1(&proof.quotient_commitment),
+            output_point: encode_scalar(&proof.output_point),
+        }
+    }
+}
+
+/// The points of an `OpeningKey` in the precompiles' encoding, for a verifier contract to store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodedOpeningKey {
+    pub g1_gen: [u8; G1_ENCODED_SIZE],
+    pub g2_gen: [u8; G2_ENCODED_SIZE],
+    pub tau_g2_gen: [u8; G2_ENCODED_SIZE],
+}
+
+impl From<&OpeningKey> for EncodedOpeningKey {
+    fn from(opening_key: &OpeningKey) -> Self {
+        EncodedOpeningKey {
+            g1_gen: encode_g1(&opening_key.g1_gen),
+            g2_gen: encode_g2(&opening_key.g2_gen),
+            tau_g2_gen: encode_g2(&opening_key.tau_g2_gen),
+        }
+    }
+}
+
+/// The input to the pairing precompile that checks the same equation as `OpeningKey::verify`
+///
+/// The two pairs are `(C - y * G1 + z * \pi, G2)` and `(-\pi, \tau * G2)`, the precompile returns one
+/// exactly when `proof` is valid for `input_point`.
+pub fn pairing_calldata(
+    opening_key: &OpeningKey,
+    proof: &Proof,
+    input_point: blstrs::Scalar,
+) -> [u8; 2 * PAIRING_PAIR_SIZE] {
+    let inner_a: blstrs::G1Affine = (proof.polynomial_commitment - (opening_key.g1_gen * proof.output_point)
+        + (proof.quotient_commitment * input_point))
+        .into();
+    let neg_quotient_commitment = -proof.quotient_commitment;
+
+    let mut calldata = [0u8; 2 * PAIRING_PAIR_SIZE];
+    let (first, second) = calldata.split_at_mut(PAIRING_PAIR_SIZE);
+    first[..G1_ENCODED_SIZE].copy_from_slice(&encode_g1(&inner_a));
+    first[G1_ENCODED_SIZE..].copy_from_slice(&encode_g2(&opening_key.g2_gen));
+    second[..G1_ENCODED_SIZE].copy_from_slice(&encode_g1(&neg_quotient_commitment));
+    second[G1_ENCODED_SIZE..].copy_from_slice(&encode_g2(&opening_key.tau_g2_gen));
+    calldata
+}
+
+// Returns the 48 byte field element, if the padding is zero
+fn unpad(padded: &[u8]) -> Result<&[u8], PointDecodingError> {
+    let (padding, coordinate) = padded.split_at(PADDED_FP_SIZE - FP_SIZE);
+    if padding.iter().any(|byte| *byte != 0) {
+        return Err(PointDecodingError::InvalidEncoding);
+    }
+    Ok(coordinate)
+}
+
+// The top three bits are flags in blst's encoding, but must be zero in a field element
+fn check_flags(first_byte: u8) -> Result<(), PointDecodingError> {
+    if first_byte >> 5 != 0 {
+        return Err(PointDecodingError::InvalidEncoding);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pairing_lib::group::Group;
+
+    use super::*;
+
+    // What the pairing precompile computes, with the points decoded from the calldata
+    #[cfg(feature = "prover")]
+    fn evaluate_pairing_calldata(calldata: &[u8]) -> Result<bool, PointDecodingError> {
+        use pairing_lib::{MillerLoopResult, MultiMillerLoop};
+
+        let mut g1_points = Vec::new();
+        let mut g2_points = Vec::new();
+        for pair in calldata.chunks_exact(PAIRING_PAIR_SIZE) {
+            let (g1, g2) = pair.split_at(G1_ENCODED_SIZE);
+            g1_points.push(decode_g1(g1.try_into().unwrap())?);
+            g2_points.push(blstrs::G2Prepared::from(decode_g2(g2.try_into().unwrap())?));
+        }
+        let terms: Vec<_> = g1_points.iter().zip(&g2_points).collect();
+        Ok(blstrs::Bls12::multi_miller_loop(&terms).final_exponentiation().is_identity().into())
+    }
+
+    #[test]
+    fn encodings_round_trip() {
+        let mut rng = rand::thread_rng();
+        for g1 in [blstrs::G1Affine::generator(), blstrs::G1Projective::random(&mut rng).into(), blstrs::G1Affine::identity()] {
+            let encoded = encode_g1(&g1);
+            assert!(encoded.chunks_exact(PADDED_FP_SIZE).all(|padded| padded[..16] == [0; 16]));
+            assert_eq!(decode_g1(&encoded), Ok(g1));
+        }
+        for g2 in [blstrs::G2Affine::generator(), blstrs::G2Projective::random(&mut rng).into(), blstrs::G2Affine::identity()] {
+            assert_eq!(decode_g2(&encode_g2(&g2)), Ok(g2));
+        }
+
+        // The generator's x coordinate, from EIP-2537
+        let encoded = encode_g1(&blstrs::G1Affine::generator());
+        assert_eq!(&encoded[16..20], &[0x17, 0xf1, 0xd3, 0xa7]);
+        // x.c0 of the G2 generator comes first
+        let encoded = encode_g2(&blstrs::G2Affine::generator());
+        assert_eq!(&encoded[16..20], &[0x02, 0x4a, 0xa2, 0xb2]);
+        assert_eq!(&encoded[80..84], &[0x13, 0xe0, 0x2b, 0x60]);
+
+        let mut bad_padding = encode_g1(&blstrs::G1Affine::generator());
+        bad_padding[0] = 1;
+        assert_eq!(decode_g1(&bad_padding), Err(PointDecodingError::InvalidEncoding));
+    }
+
+    #[cfg(feature = "prover")]
+    #[test]
+    fn pairing_calldata_matches_verify() {
+        use ff::Field;
+
+        use crate::{domain::Domain, params::PublicParameters, polynomial::Polynomial};
+
+        let domain = Domain::new(16);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+        let opening_key = &public_parameters.opening_key;
+        let poly = Polynomial::new((0..16).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect());
+        let poly_comm = public_parameters.commit_key.commit(&poly);
+
+        for input_point in [blstrs::Scalar::from(123456u64), domain.roots()[5]] {
+            let mut proof = Proof::create(&public_parameters.commit_key, &poly, poly_comm, input_point, &domain);
+            let calldata = pairing_calldata(opening_key, &proof, input_point);
+            assert_eq!(calldata.len(), 768);
+            let encoded_proof = EncodedProof::from(&proof);
+            assert_eq!(decode_g1(&encoded_proof.quotient_commitment), Ok(proof.quotient_commitment));
+            assert!(evaluate_pairing_calldata(&calldata).unwrap());
+
+            let wrong_point = pairing_calldata(opening_key, &proof, input_point.double());
+            assert!(!evaluate_pairing_calldata(&wrong_point).unwrap());
+
+            proof.output_point += blstrs::Scalar::one();
+            assert!(!proof.verify(input_point, opening_key));
+            assert!(!evaluate_pairing_calldata(&pairing_calldata(opening_key, &proof, input_point)).unwrap());
+        }
+
+        let encoded_key = EncodedOpeningKey::from(opening_key);
+        assert_eq!(decode_g2(&encoded_key.tau_g2_gen), Ok(opening_key.tau_g2_gen));
+    }
+}
@@ -0,0 +1,155 @@
+use alloc::vec::Vec;
+
+use std::sync::OnceLock;
+
+use group::prime::PrimeCurveAffine;
+
+use crate::{
+    cells::CellContext,
+    commit_key::{CommitKey, CommitKeyLagrange},
+    domain::Domain,
+    opening_key::OpeningKey,
+    params::PublicParameters,
+};
+
+pub use crate::FIELD_ELEMENTS_PER_BLOB;
+// The number of field elements in a cell of an extended mainnet blob (EIP-7594)
+pub const FIELD_ELEMENTS_PER_CELL: usize = 64;
+
+// The number of bytes needed to represent an uncompressed G1 point
+const G1_POINT_UNCOMPRESSED_SIZE: usize = 96;
+// The number of bytes needed to represent an uncompressed G2 point
+const G2_POINT_UNCOMPRESSED_SIZE: usize = 192;
+
+// These files are generated by the build script from `trusted_setup.txt`
+static G1_LAGRANGE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/g1_lagrange.bin"));
+static G1_MONOMIAL: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/g1_monomial.bin"));
+static G2_MONOMIAL: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/g2_monomial.bin"));
+
+static MAINNET_SETUP: OnceLock<(Domain, PublicParameters)> = OnceLock::new();
+static MAINNET_CELL_CONTEXT: OnceLock<CellContext> = OnceLock::new();
+
+/// Returns the domain that the mainnet trusted setup was created for
+pub fn domain() -> &'static Domain {
+    &mainnet_setup().0
+}
+
+/// Returns the public parameters from the mainnet trusted setup
+///
+/// The points are decoded on first use and cached for the lifetime of the program.
+pub fn public_parameters() -> &'static PublicParameters {
+    &mainnet_setup().1
+}
+
+/// Returns the context for computing and verifying cells of mainnet blobs
+///
+/// The precomputation for the cell proofs runs on first use.
+pub fn cell_context() -> &'static CellContext {
+    MAINNET_CELL_CONTEXT.get_or_init(|| {
+        CellContext::new(public_parameters(), FIELD_ELEMENTS_PER_BLOB, FIELD_ELEMENTS_PER_CELL)
+            .expect("the mainnet setup supports cells")
+    })
+}
+
+fn mainnet_setup() -> &'static (Domain, PublicParameters) {
+    MAINNET_SETUP.get_or_init(|| {
+        let domain = Domain::new(FIELD_ELEMENTS_PER_BLOB);
+
+        // The points were checked by the build script, so we skip the subgroup checks here
+        let lagrange_points = decode_g1_points(G1_LAGRANGE);
+        let monomial_points = decode_g1_points(G1_MONOMIAL);
+        let g2_points: Vec<_> = G2_MONOMIAL
+            .chunks_exact(G2_POINT_UNCOMPRESSED_SIZE)
+            .map(|bytes| blstrs::G2Affine::from_uncompressed_unchecked(bytes.try_into().unwrap()).unwrap())
+            .collect();
+        assert_eq!(lagrange_points.len(), domain.size());
+
+        // The ceremony uses the standard generators, so the sum of the lagrange points is the G1 generator
+        let g1_gen = blstrs::G1Affine::generator();
+        let commit_key_monomial = CommitKey::new(monomial_points);
+        let commit_key = CommitKeyLagrange::new(lagrange_points);
+        let opening_key = OpeningKey::new(g1_gen, g2_points[0], g2_points[1]);
+
+        let public_parameters =
+            PublicParameters::new(commit_key_monomial, commit_key, opening_key).with_g2_monomial(g2_points);
+
+        (domain, public_parameters)
+    })
+}
+
+fn decode_g1_points(bytes: &[u8]) -> Vec<blstrs::G1Affine> {
+    bytes
+        .chunks_exact(G1_POINT_UNCOMPRESSED_SIZE)
+        .map(|bytes| blstrs::G1Affine::from_uncompressed_unchecked(bytes.try_into().unwrap()).unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use group::Group;
+
+    use crate::{polynomial::Polynomial, proof::Proof};
+
+    use super::*;
+
+    #[test]
+    fn lagrange_points_sum_to_generator() {
+        let public_parameters = public_parameters();
+
+        // The sum of all lagrange polynomials is the constant polynomial 1
+        let one = Polynomial::new(vec![blstrs::Scalar::one(); domain().size()]);
+        let commitment = public_parameters.commit_key.commit(&one);
+        assert_eq!(commitment, blstrs::G1Affine::generator());
+        assert_eq!(public_parameters.opening_key.g2_gen, blstrs::G2Affine::generator());
+        assert_eq!(public_parameters.commit_key_monomial.points()[0], blstrs::G1Affine::generator());
+    }
+
+    #[test]
+    fn smaller_domain_keys_from_mainnet_setup() {
+        let public_parameters = public_parameters();
+        let domain = Domain::new(1024);
+        let commit_key = public_parameters.commit_key_for_domain(&domain);
+
+        let one = Polynomial::new(vec![blstrs::Scalar::one(); domain.size()]);
+        assert_eq!(commit_key.commit(&one), blstrs::G1Affine::generator());
+    }
+
+    #[test]
+    fn mainnet_cells_smoke() {
+        let public_parameters = public_parameters();
+        let cell_context = cell_context();
+        assert_eq!(cell_context.num_cells(), 128);
+
+        let blob = Polynomial::new((0..domain().size() as u64).map(blstrs::Scalar::from).collect());
+        let commitment = public_parameters.commit_key.commit(&blob);
+        let (cells, proofs) = cell_context.compute_cells_and_kzg_proofs(&blob).unwrap();
+
+        let cell_indices = [0, 1, 64, 127];
+        let valid = cell_context
+            .verify_cell_kzg_proof_batch(
+                &[commitment; 4],
+                &cell_indices,
+                &cell_indices.map(|cell_index| cells[cell_index].clone()),
+                &cell_indices.map(|cell_index| proofs[cell_index]),
+                rand::thread_rng(),
+            )
+            .unwrap();
+        assert!(valid);
+    }
+
+    #[test]
+    fn mainnet_proof_smoke() {
+        let domain = domain();
+        let public_parameters = public_parameters();
+
+        let poly = Polynomial::new((0..domain.size() as u64).map(blstrs::Scalar::from).collect());
+        let poly_comm = public_parameters.commit_key.commit(&poly);
+        assert_ne!(poly_comm, blstrs::G1Projective::identity().into());
+
+        let input_point = blstrs::Scalar::from(123456u64);
+        let proof = Proof::create(&public_parameters.commit_key, &poly, poly_comm, input_point, domain);
+        assert!(proof.verify(input_point, &public_parameters.opening_key));
+        assert!(!proof.verify(input_point.double(), &public_parameters.opening_key));
+    }
+}
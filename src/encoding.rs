@@ -0,0 +1,529 @@
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+use crate::{polynomial::Polynomial, FIELD_ELEMENTS_PER_BLOB, SCALAR_SERIALIZED_SIZE};
+
+// The number of bytes in the length prefix of a payload
+const LENGTH_PREFIX_SIZE: usize = 8;
+// The number of bits that fit in a field element, the modulus is between 2^254 and 2^255
+const BITS_PER_FIELD_ELEMENT: usize = 254;
+
+/// How the bytes of a payload are packed into field elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Packing {
+    /// 31 bytes per field element, as the low bytes of the big endian scalar.
+    /// This is simple to decode, but leaves 2 bits per field element unused.
+    Bytes31,
+    /// 254 bits per field element, the payload is read as one big endian bit string
+    Bits254,
+}
+
+/// Errors that can occur when decoding blobs back into a payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodingError {
+    /// There were no blobs to decode
+    NoBlobs,
+    /// The blob does not have the number of field elements that the codec uses
+    BlobSizeMismatch { blob_size: usize, polynomial_size: usize },
+    /// The field element uses bits that the packing leaves empty
+    InvalidFieldElement { blob: usize, index: usize },
+    /// The length prefix is larger than the payload that fits in the blobs
+    LengthTooLarge { length: u64, capacity: usize },
+    /// The bytes after the payload are not zero, or there are more blobs than the payload needs
+    NonCanonicalPadding,
+}
+
+impl core::fmt::Display for DecodingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodingError::NoBlobs => write!(f, "there are no blobs to decode"),
+            DecodingError::BlobSizeMismatch { blob_size, polynomial_size } => write!(
+                f,
+                "blob has {polynomial_size} field elements, but blobs have {blob_size} field elements"
+            ),
+            DecodingError::InvalidFieldElement { blob, index } => {
+                write!(f, "field element {index} of blob {blob} is not a valid packing")
+            }
+            DecodingError::LengthTooLarge { length, capacity } => {
+                write!(f, "payload length {length} is larger than the {capacity} bytes in the blobs")
+            }
+            DecodingError::NonCanonicalPadding => write!(f, "padding after the payload is not canonical"),
+        }
+    }
+}
+
+impl core::error::Error for DecodingError {}
+
+/// Encodes arbitrary payloads into blobs, and decodes them back
+///
+/// The payload is prefixed with its length as an 8 byte big endian integer, packed into field elements
+/// and split over as many blobs as needed. The rest of the last blob is zero. Decoding is the exact
+/// inverse: every set of blobs decodes to at most one payload, and any other padding is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadCodec {
+    blob_size: usize,
+    packing: Packing,
+}
+
+impl Default for PayloadCodec {
+    fn default() -> Self {
+        PayloadCodec::new(FIELD_ELEMENTS_PER_BLOB, Packing::Bytes31)
+    }
+}
+
+impl PayloadCodec {
+    /// Creates a codec for blobs of `blob_size` field elements
+    ///
+    /// Panics, if `blob_size` is not a power of two, like the size of every `Domain`,
+    /// or if a blob cannot hold the length prefix
+    pub fn new(blob_size: usize, packing: Packing) -> Self {
+        assert!(blob_size.is_power_of_two(), "blob size {blob_size} is not a power of two");
+        let codec = PayloadCodec { blob_size, packing };
+        assert!(codec.bytes_per_blob() >= LENGTH_PREFIX_SIZE, "blobs of {blob_size} field elements are too small");
+        codec
+    }
+
+    pub fn blob_size(&self) -> usize {
+        self.blob_size
+    }
+
+    pub fn packing(&self) -> Packing {
+        self.packing
+    }
+
+    /// The number of payload bytes that fit in one blob, including the length prefix
+    pub fn bytes_per_blob(&self) -> usize {
+        match self.packing {
+            Packing::Bytes31 => self.blob_size * (SCALAR_SERIALIZED_SIZE - 1),
+            Packing::Bits254 => self.blob_size * BITS_PER_FIELD_ELEMENT / 8,
+        }
+    }
+
+    /// The number of blobs needed for a payload of `payload_len` bytes
+    pub fn num_blobs(&self, payload_len: usize) -> usize {
+        (LENGTH_PREFIX_SIZE + payload_len).div_ceil(self.bytes_per_blob())
+    }
+
+    /// Encodes the payload into blobs
+    pub fn encode_payload(&self, payload: &[u8]) -> Vec<Polynomial> {
+        let mut bytes = Vec::with_capacity(self.num_blobs(payload.len()) * self.bytes_per_blob());
+        bytes.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+        bytes.extend_from_slice(payload);
+        bytes.resize(self.num_blobs(payload.len()) * self.bytes_per_blob(), 0);
+
+        bytes.chunks_exact(self.bytes_per_blob()).map(|chunk| self.pack(chunk)).collect()
+    }
+
+    /// Encodes the payload that `reader` returns until it is exhausted
+    ///
+    /// The payload is read one blob at a time, so it is never held in memory as bytes. The length is
+    /// only known at the end, so the raw bytes of the first blob are kept until then.
+    #[cfg(feature = "std")]
+    pub fn encode_reader(&self, mut reader: impl Read) -> io::Result<Vec<Polynomial>> {
+        let bytes_per_blob = self.bytes_per_blob();
+
+        let mut first_chunk = vec![0u8; bytes_per_blob];
+        let mut payload_len = read_up_to(&mut reader, &mut first_chunk[LENGTH_PREFIX_SIZE..])?;
+
+        let mut remaining_blobs = Vec::new();
+        if payload_len == bytes_per_blob - LENGTH_PREFIX_SIZE {
+            let mut chunk = vec![0u8; bytes_per_blob];
+            loop {
+                let read = read_up_to(&mut reader, &mut chunk)?;
+                if read == 0 {
+                    break;
+                }
+                chunk[read..].fill(0);
+                remaining_blobs.push(self.pack(&chunk));
+                payload_len += read;
+                if read < bytes_per_blob {
+                    break;
+                }
+            }
+        }
+
+        first_chunk[..LENGTH_PREFIX_SIZE].copy_from_slice(&(payload_len as u64).to_be_bytes());
+        let mut blobs = vec![self.pack(&first_chunk)];
+        blobs.extend(remaining_blobs);
+        Ok(blobs)
+    }
+
+    /// Decodes the payload from blobs that were created by `encode_payload`
+    pub fn decode_payload(&self, blobs: &[Polynomial]) -> Result<Vec<u8>, DecodingError> {
+        if blobs.is_empty() {
+            return Err(DecodingError::NoBlobs);
+        }
+
+        let mut bytes = Vec::with_capacity(blobs.len() * self.bytes_per_blob());
+        for (blob_index, blob) in blobs.iter().enumerate() {
+            if blob.evaluations.len() != self.blob_size {
+                return Err(DecodingError::BlobSizeMismatch {
+                    blob_size: self.blob_size,
+                    polynomial_size: blob.evaluations.len(),
+                });
+            }
+            self.unpack(blob, blob_index, &mut bytes)?;
+        }
+
+        let length = u64::from_be_bytes(bytes[..LENGTH_PREFIX_SIZE].try_into().unwrap());
+        let capacity = bytes.len() - LENGTH_PREFIX_SIZE;
+        if length > capacity as u64 {
+            return Err(DecodingError::LengthTooLarge { length, capacity });
+        }
+        let length = length as usize;
+        if blobs.len() != self.num_blobs(length) {
+            return Err(DecodingError::NonCanonicalPadding);
+        }
+
+        let mut payload = bytes.split_off(LENGTH_PREFIX_SIZE);
+        if payload[length..].iter().any(|byte| *byte != 0) {
+            return Err(DecodingError::NonCanonicalPadding);
+        }
+        payload.truncate(length);
+        Ok(payload)
+    }
+
+    /// Splits a range of the payload into ranges of the blobs' bytes, one for each blob it touches
+    ///
+    /// The returned ranges are offsets into the `bytes_per_blob` bytes of each blob, which start with
+    /// the length prefix in the first blob.
+    pub fn locate_payload_range(&self, payload_range: Range<usize>) -> Vec<(usize, Range<usize>)> {
+        let bytes_per_blob = self.bytes_per_blob();
+        let mut start = payload_range.start + LENGTH_PREFIX_SIZE;
+        let end = payload_range.end + LENGTH_PREFIX_SIZE;
+
+        let mut ranges = Vec::new();
+        while start < end {
+            let blob_index = start / bytes_per_blob;
+            let blob_end = ((blob_index + 1) * bytes_per_blob).min(end);
+            ranges.push((blob_index, start - blob_index * bytes_per_blob..blob_end - blob_index * bytes_per_blob));
+            start = blob_end;
+        }
+        ranges
+    }
+
+    /// Returns the indices of the field elements that hold the bytes `byte_range` of a blob
+    ///
+    /// Panics, if the range is not within the `bytes_per_blob` bytes of a blob
+    pub fn field_element_range(&self, byte_range: Range<usize>) -> Range<usize> {
+        assert!(
+            byte_range.start <= byte_range.end && byte_range.end <= self.bytes_per_blob(),
+            "byte range {byte_range:?} is not within the {} bytes of a blob",
+            self.bytes_per_blob()
+        );
+        if byte_range.is_empty() {
+            return 0..0;
+        }
+        match self.packing {
+            Packing::Bytes31 => {
+                let usable = SCALAR_SERIALIZED_SIZE - 1;
+                byte_range.start / usable..byte_range.end.div_ceil(usable)
+            }
+            Packing::Bits254 => {
+                8 * byte_range.start / BITS_PER_FIELD_ELEMENT..(8 * byte_range.end).div_ceil(BITS_PER_FIELD_ELEMENT)
+            }
+        }
+    }
+
+    /// Returns the bytes `byte_range` of a blob, given the field elements from `field_element_range`
+    ///
+    /// Returns `None`, if the number of field elements does not match or one of them is not a valid packing.
+    pub fn unpack_byte_range(&self, byte_range: Range<usize>, field_elements: &[blstrs::Scalar]) -> Option<Vec<u8>> {
+        let indices = self.field_element_range(byte_range.clone());
+        if field_elements.len() != indices.len() {
+            return None;
+        }
+        if byte_range.is_empty() {
+            return Some(Vec::new());
+        }
+
+        // The bytes of the covering field elements, starting at the blob byte `first_byte`
+        let (first_byte, bytes) = match self.packing {
+            Packing::Bytes31 => {
+                let mut bytes = Vec::with_capacity(field_elements.len() * (SCALAR_SERIALIZED_SIZE - 1));
+                for evaluation in field_elements {
+                    let scalar_bytes = evaluation.to_bytes_be();
+                    if scalar_bytes[0] != 0 {
+                        return None;
+                    }
+                    bytes.extend_from_slice(&scalar_bytes[1..]);
+                }
+                (indices.start * (SCALAR_SERIALIZED_SIZE - 1), bytes)
+            }
+            Packing::Bits254 => {
+                let first_bit = indices.start * BITS_PER_FIELD_ELEMENT;
+                let shift = first_bit % 8;
+                let mut bytes = vec![0u8; (shift + field_elements.len() * BITS_PER_FIELD_ELEMENT).div_ceil(8) + SCALAR_SERIALIZED_SIZE];
+                for (index, evaluation) in field_elements.iter().enumerate() {
+                    let scalar_bytes = evaluation.to_bytes_be();
+                    if scalar_bytes[0] >> 6 != 0 {
+                        return None;
+                    }
+                    write_bits(&mut bytes, shift + index * BITS_PER_FIELD_ELEMENT, &scalar_bytes);
+                }
+                (first_bit / 8, bytes)
+            }
+        };
+        Some(bytes[byte_range.start - first_byte..byte_range.end - first_byte].to_vec())
+    }
+
+    // Packs exactly `bytes_per_blob` bytes into a blob
+    fn pack(&self, chunk: &[u8]) -> Polynomial {
+        let evaluations = (0..self.blob_size)
+            .map(|index| {
+                let scalar_bytes = match self.packing {
+                    Packing::Bytes31 => {
+                        let mut scalar_bytes = [0u8; SCALAR_SERIALIZED_SIZE];
+                        let usable = SCALAR_SERIALIZED_SIZE - 1;
+                        scalar_bytes[1..].copy_from_slice(&chunk[index * usable..][..usable]);
+                        scalar_bytes
+                    }
+                    Packing::Bits254 => read_bits(chunk, index * BITS_PER_FIELD_ELEMENT),
+                };
+                // The top bits are zero, so the scalar is smaller than the modulus
+                blstrs::Scalar::from_bytes_be(&scalar_bytes).unwrap()
+            })
+            .collect();
+        Polynomial::new(evaluations)
+    }
+
+    // Appends the `bytes_per_blob` bytes in the blob to `bytes`
+    fn unpack(&self, blob: &Polynomial, blob_index: usize, bytes: &mut Vec<u8>) -> Result<(), DecodingError> {
+        match self.packing {
+            Packing::Bytes31 => {
+                for (index, evaluation) in blob.evaluations.iter().enumerate() {
+                    let scalar_bytes = evaluation.to_bytes_be();
+                    if scalar_bytes[0] != 0 {
+                        return Err(DecodingError::InvalidFieldElement { blob: blob_index, index });
+                    }
+                    bytes.extend_from_slice(&scalar_bytes[1..]);
+                }
+            }
+            Packing::Bits254 => {
+                // Enough room for the last field element, the bits after the blob's bytes must be zero
+                let total_bits = self.blob_size * BITS_PER_FIELD_ELEMENT;
+                let mut chunk = vec![0u8; total_bits.div_ceil(8) + SCALAR_SERIALIZED_SIZE];
+                for (index, evaluation) in blob.evaluations.iter().enumerate() {
+                    let scalar_bytes = evaluation.to_bytes_be();
+                    if scalar_bytes[0] >> 6 != 0 {
+                        return Err(DecodingError::InvalidFieldElement { blob: blob_index, index });
+                    }
+                    write_bits(&mut chunk, index * BITS_PER_FIELD_ELEMENT, &scalar_bytes);
+                }
+
+                let bytes_per_blob = self.bytes_per_blob();
+                let trailing_bits = total_bits - 8 * bytes_per_blob;
+                if trailing_bits > 0 && chunk[bytes_per_blob] != 0 {
+                    return Err(DecodingError::NonCanonicalPadding);
+                }
+                bytes.extend_from_slice(&chunk[..bytes_per_blob]);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encodes the payload into mainnet sized blobs, with 31 bytes per field element
+pub fn encode_payload(payload: &[u8]) -> Vec<Polynomial> {
+    PayloadCodec::default().encode_payload(payload)
+}
+
+/// Decodes a payload from blobs that were created by `encode_payload`
+pub fn decode_payload(blobs: &[Polynomial]) -> Result<Vec<u8>, DecodingError> {
+    PayloadCodec::default().decode_payload(blobs)
+}
+
+// Fills `buffer` from `reader`, stopping early only at the end of the input
+#[cfg(feature = "std")]
+fn read_up_to(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(filled)
+}
+
+// Returns the 254 bits of `bytes` starting at `bit_offset` as a big endian scalar, so the top
+// two bits are zero. Bits past the end of `bytes` are zero.
+fn read_bits(bytes: &[u8], bit_offset: usize) -> [u8; SCALAR_SERIALIZED_SIZE] {
+    let start = bit_offset / 8;
+    let shift = bit_offset % 8;
+    let byte_at = |index: usize| bytes.get(start + index).copied().unwrap_or(0);
+
+    // The 256 bits starting at the offset
+    let mut window = [0u8; SCALAR_SERIALIZED_SIZE];
+    for (index, byte) in window.iter_mut().enumerate() {
+        *byte = if shift == 0 {
+            byte_at(index)
+        } else {
+            (byte_at(index) << shift) | (byte_at(index + 1) >> (8 - shift))
+        };
+    }
+
+    // Keep the top 254 bits, moved down into the low bits
+    let mut scalar_bytes = [0u8; SCALAR_SERIALIZED_SIZE];
+    for index in (0..SCALAR_SERIALIZED_SIZE).rev() {
+        let carry = if index > 0 { window[index - 1] << 6 } else { 0 };
+        scalar_bytes[index] = (window[index] >> 2) | carry;
+    }
+    scalar_bytes
+}
+
+// The inverse of `read_bits`, writes the low 254 bits of the big endian scalar into `bytes` at `bit_offset`.
+// `bytes` must be zero at those bits, and have room for 256 bits at the offset.
+fn write_bits(bytes: &mut [u8], bit_offset: usize, scalar_bytes: &[u8; SCALAR_SERIALIZED_SIZE]) {
+    let start = bit_offset / 8;
+    let shift = bit_offset % 8;
+
+    // Move the 254 bits to the top, the low two bits are zero
+    let mut window = [0u8; SCALAR_SERIALIZED_SIZE];
+    for index in 0..SCALAR_SERIALIZED_SIZE {
+        let carry = scalar_bytes.get(index + 1).map_or(0, |next| next >> 6);
+        window[index] = (scalar_bytes[index] << 2) | carry;
+    }
+
+    for (index, byte) in window.iter().enumerate() {
+        bytes[start + index] |= byte >> shift;
+        if shift > 0 {
+            bytes[start + index + 1] |= byte << (8 - shift);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|index| (index * 7 + 3) as u8).collect()
+    }
+
+    #[test]
+    fn round_trip_at_boundaries() {
+        for packing in [Packing::Bytes31, Packing::Bits254] {
+            let codec = PayloadCodec::new(16, packing);
+            let bytes_per_blob = codec.bytes_per_blob();
+            let usable = SCALAR_SERIALIZED_SIZE - 1;
+
+            let lengths = [
+                0,
+                1,
+                usable - LENGTH_PREFIX_SIZE,
+                usable,
+                SCALAR_SERIALIZED_SIZE,
+                SCALAR_SERIALIZED_SIZE + 1,
+                bytes_per_blob - LENGTH_PREFIX_SIZE - 1,
+                bytes_per_blob - LENGTH_PREFIX_SIZE,
+                bytes_per_blob - LENGTH_PREFIX_SIZE + 1,
+                3 * bytes_per_blob,
+            ];
+            for len in lengths {
+                let payload = payload(len);
+                let blobs = codec.encode_payload(&payload);
+                assert_eq!(blobs.len(), codec.num_blobs(len));
+                assert_eq!(codec.decode_payload(&blobs).unwrap(), payload, "{packing:?}, {len} bytes");
+
+                // Streaming in small reads gives the same blobs
+                #[cfg(feature = "std")]
+                {
+                    let reader = io::BufReader::with_capacity(7, payload.as_slice());
+                    assert_eq!(codec.encode_reader(reader).unwrap(), blobs);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "blob size 100 is not a power of two")]
+    fn blob_size_must_be_a_power_of_two() {
+        PayloadCodec::new(100, Packing::Bytes31);
+    }
+
+    #[test]
+    fn bit_packing_uses_every_bit() {
+        let codec = PayloadCodec::new(4, Packing::Bits254);
+        assert_eq!(codec.bytes_per_blob(), 127);
+
+        // All ones, the top two bits of each field element are still zero
+        let payload = vec![0xff; 127 - LENGTH_PREFIX_SIZE];
+        let blobs = codec.encode_payload(&payload);
+        assert_eq!(blobs.len(), 1);
+        for evaluation in &blobs[0].evaluations[1..] {
+            assert_eq!(evaluation.to_bytes_be()[0], 0x3f);
+        }
+        assert_eq!(codec.decode_payload(&blobs).unwrap(), payload);
+    }
+
+    #[test]
+    fn decoding_rejects_non_canonical_blobs() {
+        let codec = PayloadCodec::new(16, Packing::Bytes31);
+        let mut blobs = codec.encode_payload(&payload(10));
+
+        // A byte after the payload
+        let mut padded = blobs.clone();
+        padded[0].evaluations[1] = blstrs::Scalar::from(1u64);
+        assert_eq!(codec.decode_payload(&padded), Err(DecodingError::NonCanonicalPadding));
+
+        // An extra blob
+        padded = blobs.clone();
+        padded.push(Polynomial::new(vec![blstrs::Scalar::from(0u64); 16]));
+        assert_eq!(codec.decode_payload(&padded), Err(DecodingError::NonCanonicalPadding));
+
+        // A field element with the top byte set
+        blobs[0].evaluations[3] = -blstrs::Scalar::from(1u64);
+        assert_eq!(
+            codec.decode_payload(&blobs),
+            Err(DecodingError::InvalidFieldElement { blob: 0, index: 3 })
+        );
+
+        // A length that does not fit
+        let blobs = PayloadCodec::new(16, Packing::Bytes31).encode_payload(&[]);
+        let mut prefix = blobs[0].evaluations[0].to_bytes_be();
+        prefix[1..9].copy_from_slice(&u64::MAX.to_be_bytes());
+        let mut too_long = blobs.clone();
+        too_long[0].evaluations[0] = blstrs::Scalar::from_bytes_be(&prefix).unwrap();
+        assert_eq!(
+            codec.decode_payload(&too_long),
+            Err(DecodingError::LengthTooLarge { length: u64::MAX, capacity: 16 * 31 - 8 })
+        );
+
+        assert_eq!(codec.decode_payload(&[]), Err(DecodingError::NoBlobs));
+    }
+
+    #[test]
+    fn byte_ranges_map_to_covering_field_elements() {
+        for packing in [Packing::Bytes31, Packing::Bits254] {
+            let codec = PayloadCodec::new(16, packing);
+            let payload = payload(2 * codec.bytes_per_blob());
+            let blobs = codec.encode_payload(&payload);
+
+            for payload_range in [0..1, 20..90, 100..codec.bytes_per_blob() + 30, 0..payload.len()] {
+                let mut bytes = Vec::new();
+                for (blob_index, byte_range) in codec.locate_payload_range(payload_range.clone()) {
+                    let indices = codec.field_element_range(byte_range.clone());
+                    let field_elements = &blobs[blob_index].evaluations[indices];
+                    bytes.extend(codec.unpack_byte_range(byte_range, field_elements).unwrap());
+                }
+                assert_eq!(bytes, payload[payload_range], "{packing:?}");
+            }
+
+            // Empty ranges are covered by no field elements, wherever they start
+            for byte_range in [5..5, 40..40, codec.bytes_per_blob()..codec.bytes_per_blob()] {
+                assert_eq!(codec.field_element_range(byte_range.clone()), 0..0);
+                assert_eq!(codec.unpack_byte_range(byte_range, &[]), Some(vec![]), "{packing:?}");
+            }
+        }
+
+        let codec = PayloadCodec::new(16, Packing::Bytes31);
+        assert_eq!(codec.field_element_range(30..32), 0..2);
+        assert_eq!(codec.locate_payload_range(0..0), vec![]);
+        assert_eq!(codec.unpack_byte_range(0..40, &[blstrs::Scalar::from(0u64)]), None);
+    }
+}
@@ -0,0 +1,223 @@
+use alloc::{string::String, vec::Vec};
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "prover")]
+use crate::{
+    context::{KzgContext, KzgContextError},
+    serialization::deserialize_g1_points,
+};
+use crate::{
+    hex::{decode_hex, encode_hex},
+    serialization::{BlobDecodingError, PointDecodingError},
+    ssz::{Blob, KzgCommitment, KzgProof, Ssz},
+};
+
+/// Errors that can occur when verifying a blobs bundle, with the index of the first entry that failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleError {
+    /// There is not one commitment and one proof per blob
+    LengthMismatch { num_commitments: usize, num_proofs: usize, num_blobs: usize },
+    /// The blob has a field element that is not canonical
+    InvalidBlob { index: usize, error: BlobDecodingError },
+    /// The commitment is not the commitment to the blob
+    CommitmentMismatch { index: usize },
+    /// The proof is not a valid compressed point
+    InvalidProofEncoding { index: usize, error: PointDecodingError },
+    /// The proof does not verify against the blob and its commitment
+    InvalidProof { index: usize },
+    #[cfg(feature = "prover")]
+    Context(KzgContextError),
+}
+
+impl core::fmt::Display for BundleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BundleError::LengthMismatch { num_commitments, num_proofs, num_blobs } => write!(
+                f,
+                "bundle has {num_commitments} commitments and {num_proofs} proofs for {num_blobs} blobs"
+            ),
+            BundleError::InvalidBlob { index, error } => write!(f, "blob {index}: {error}"),
+            BundleError::CommitmentMismatch { index } => write!(f, "commitment {index} does not match its blob"),
+            BundleError::InvalidProofEncoding { index, error } => write!(f, "proof {index}: {error}"),
+            BundleError::InvalidProof { index } => write!(f, "proof {index} does not verify"),
+            #[cfg(feature = "prover")]
+            BundleError::Context(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl core::error::Error for BundleError {}
+
+#[cfg(feature = "prover")]
+impl From<KzgContextError> for BundleError {
+    fn from(error: KzgContextError) -> Self {
+        BundleError::Context(error)
+    }
+}
+
+/// The `blobsBundle` that `engine_getPayload` returns, `BlobsBundleV1` in the Engine API
+///
+/// Every value is a 0x prefixed hex string with exactly two digits per byte.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobsBundle {
+    pub commitments: Vec<KzgCommitment>,
+    pub proofs: Vec<KzgProof>,
+    pub blobs: Vec<Blob>,
+}
+
+#[cfg(feature = "prover")]
+impl BlobsBundle {
+    /// Checks that every commitment is the commitment to its blob, and that every proof verifies
+    ///
+    /// The commitments are recomputed from the blobs rather than decompressed. The proofs are
+    /// verified in one batch, and only if the batch fails, one by one to find the first invalid proof.
+    pub fn verify(&self, context: &KzgContext) -> Result<(), BundleError> {
+        let num_blobs = self.blobs.len();
+        if self.commitments.len() != num_blobs || self.proofs.len() != num_blobs {
+            return Err(BundleError::LengthMismatch {
+                num_commitments: self.commitments.len(),
+                num_proofs: self.proofs.len(),
+                num_blobs,
+            });
+        }
+
+        let blobs = self
+            .blobs
+            .iter()
+            .enumerate()
+            .map(|(index, blob)| blob.to_polynomial().map_err(|error| BundleError::InvalidBlob { index, error }))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut commitments = Vec::with_capacity(num_blobs);
+        for (index, (blob, commitment)) in blobs.iter().zip(&self.commitments).enumerate() {
+            let recomputed = context.commit(blob)?;
+            if KzgCommitment::from(recomputed) != *commitment {
+                return Err(BundleError::CommitmentMismatch { index });
+            }
+            commitments.push(recomputed);
+        }
+
+        let proof_bytes: Vec<_> = self.proofs.iter().map(|proof| proof.0).collect();
+        let proofs = deserialize_g1_points(&proof_bytes)
+            .into_iter()
+            .enumerate()
+            .map(|(index, proof)| proof.map_err(|error| BundleError::InvalidProofEncoding { index, error }))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if context.verify_blob_kzg_proof_batch(&blobs, &commitments, &proofs)? {
+            return Ok(());
+        }
+        for (index, ((blob, commitment), proof)) in blobs.iter().zip(&commitments).zip(&proofs).enumerate() {
+            if !context.verify_blob_kzg_proof(blob, *commitment, *proof)? {
+                return Err(BundleError::InvalidProof { index });
+            }
+        }
+        unreachable!("the batch failed, so one of the proofs must be invalid")
+    }
+}
+
+// Serializes a newtype around a byte array as a 0x prefixed hex string
+macro_rules! impl_hex_serde {
+    ($name:ident) => {
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&encode_hex(&self.0[..]))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let string = String::deserialize(deserializer)?;
+                let bytes = decode_hex(&string, <$name as Ssz>::SSZ_SIZE).map_err(D::Error::custom)?;
+                Ok($name(bytes.try_into().unwrap()))
+            }
+        }
+    };
+}
+
+impl_hex_serde!(KzgCommitment);
+impl_hex_serde!(KzgProof);
+impl_hex_serde!(Blob);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hex::HexError, BYTES_PER_BLOB, G1_POINT_SERIALIZED_SIZE};
+
+    fn bundle_json(commitment: &str, proof: &str, blob: &str) -> String {
+        format!(r#"{{"commitments":["{commitment}"],"proofs":["{proof}"],"blobs":["{blob}"]}}"#)
+    }
+
+    #[test]
+    fn parse_strict_hex() {
+        let commitment = encode_hex(&[1; G1_POINT_SERIALIZED_SIZE]);
+        let proof = encode_hex(&[2; G1_POINT_SERIALIZED_SIZE]);
+        let blob = encode_hex(&[0; BYTES_PER_BLOB]);
+
+        let json = bundle_json(&commitment, &proof, &blob);
+        let bundle: BlobsBundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(bundle.commitments, vec![KzgCommitment([1; G1_POINT_SERIALIZED_SIZE])]);
+        assert_eq!(bundle.proofs, vec![KzgProof([2; G1_POINT_SERIALIZED_SIZE])]);
+        assert_eq!(bundle.blobs[0].0[..], [0; BYTES_PER_BLOB]);
+        assert_eq!(serde_json::to_string(&bundle).unwrap(), json);
+
+        let errors = [
+            (bundle_json(&commitment[2..], &proof, &blob), HexError::MissingPrefix.to_string()),
+            (
+                bundle_json(&commitment, &proof[..proof.len() - 1], &blob),
+                HexError::InvalidLength { expected: 96, received: 95 }.to_string(),
+            ),
+            (bundle_json(&commitment, &proof, &format!("{blob}00")), "expected 262144 hex digits".to_string()),
+            (bundle_json(&commitment.replace('1', "g"), &proof, &blob), HexError::InvalidCharacter { index: 3 }.to_string()),
+        ];
+        for (json, message) in errors {
+            let error = serde_json::from_str::<BlobsBundle>(&json).unwrap_err();
+            assert!(error.to_string().contains(&message), "{error}");
+        }
+    }
+
+    #[cfg(feature = "embedded-setup")]
+    #[test]
+    fn verify_mainnet_bundle() {
+        use crate::{domain::Domain, embedded_setup, polynomial::Polynomial, FIELD_ELEMENTS_PER_BLOB};
+
+        let context = KzgContext::from_public_parameters(
+            embedded_setup::public_parameters(),
+            Domain::new(FIELD_ELEMENTS_PER_BLOB),
+        )
+        .unwrap();
+
+        let mut bundle = BlobsBundle { commitments: Vec::new(), proofs: Vec::new(), blobs: Vec::new() };
+        for seed in 0..3u64 {
+            let polynomial =
+                Polynomial::new((0..FIELD_ELEMENTS_PER_BLOB as u64).map(|index| blstrs::Scalar::from(index * seed)).collect());
+            let commitment = context.commit(&polynomial).unwrap();
+            bundle.proofs.push(context.compute_blob_kzg_proof(&polynomial, commitment).unwrap().into());
+            bundle.commitments.push(commitment.into());
+            bundle.blobs.push(Blob::from_polynomial(&polynomial));
+        }
+        assert_eq!(bundle.verify(&context), Ok(()));
+
+        let mut swapped = bundle.clone();
+        swapped.proofs.swap(1, 2);
+        assert_eq!(swapped.verify(&context), Err(BundleError::InvalidProof { index: 1 }));
+
+        let mut wrong_commitment = bundle.clone();
+        wrong_commitment.commitments[2] = bundle.commitments[0];
+        assert_eq!(wrong_commitment.verify(&context), Err(BundleError::CommitmentMismatch { index: 2 }));
+
+        let mut invalid_proof = bundle.clone();
+        invalid_proof.proofs[0].0[0] &= 0x7f;
+        assert_eq!(
+            invalid_proof.verify(&context),
+            Err(BundleError::InvalidProofEncoding { index: 0, error: PointDecodingError::InvalidEncoding })
+        );
+
+        bundle.proofs.pop();
+        assert_eq!(
+            bundle.verify(&context),
+            Err(BundleError::LengthMismatch { num_commitments: 3, num_proofs: 2, num_blobs: 3 })
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use alloc::{vec, vec::Vec};
+
+use group::{prime::PrimeCurveAffine, Curve, Group};
+
+use crate::msm::{accumulate_buckets, num_bits, scalar_to_limbs, signed_digits, sum_buckets};
+
+// Scalars are less than the field modulus, which is less than 2^255
+const SCALAR_NUM_BITS: usize = 255;
+
+/// Precomputed multiples of a fixed set of points, used to speed up commitments
+///
+/// For every point `P_i` and every window `j`, the table stores `2^(c * j) * P_i`.
+/// A multi-scalar multiplication then only needs to look up the multiple for each
+/// signed digit and add it into a bucket. There are no doublings and only one set
+/// of buckets, instead of one set per window.
+///
+/// The window size `c` trades memory for speed: larger windows need fewer table
+/// entries and fewer additions, but more buckets to sum up at the end.
+#[derive(Debug, Clone)]
+pub struct FixedBaseTable {
+    window_bits: usize,
+    num_windows: usize,
+    num_points: usize,
+    // `num_windows` consecutive multiples for each point
+    multiples: Vec<blstrs::G1Affine>,
+}
+
+impl FixedBaseTable {
+    /// Precomputes the table for `points` with windows of `window_bits` bits
+    pub fn new(points: &[blstrs::G1Affine], window_bits: usize) -> FixedBaseTable {
+        assert!(
+            (2..=24).contains(&window_bits),
+            "window size must be between 2 and 24 bits, got {window_bits}"
+        );
+
+        let num_windows = Self::num_windows(window_bits);
+        let mut multiples = Vec::with_capacity(points.len() * num_windows);
+        for point in points {
+            let mut multiple = blstrs::G1Projective::from(point);
+            for _ in 0..num_windows {
+                multiples.push(multiple);
+                for _ in 0..window_bits {
+                    multiple = multiple.double();
+                }
+            }
+        }
+
+        let mut multiples_affine = vec![blstrs::G1Affine::identity(); multiples.len()];
+        blstrs::G1Projective::batch_normalize(&multiples, &mut multiples_affine);
+
+        FixedBaseTable { window_bits, num_windows, num_points: points.len(), multiples: multiples_affine }
+    }
+
+    /// Returns the number of bytes that a table for `num_points` points with
+    /// windows of `window_bits` bits occupies, without building it
+    pub fn estimate_memory_footprint(num_points: usize, window_bits: usize) -> usize {
+        num_points * Self::num_windows(window_bits) * core::mem::size_of::<blstrs::G1Affine>()
+    }
+
+    /// Returns the number of bytes used by the precomputed multiples
+    pub fn memory_footprint(&self) -> usize {
+        self.multiples.len() * core::mem::size_of::<blstrs::G1Affine>()
+    }
+
+    pub fn window_bits(&self) -> usize {
+        self.window_bits
+    }
+
+    pub fn num_points(&self) -> usize {
+        self.num_points
+    }
+
+    /// Computes \sum scalars[i] * points[i], where `points` are the points the table was built for
+    pub fn msm(&self, scalars: &[blstrs::Scalar]) -> blstrs::G1Projective {
+        assert_eq!(self.num_points, scalars.len());
+        self.msm_sparse(scalars.iter().enumerate())
+    }
+
+    /// Computes \sum scalar * points[index] over the `(index, scalar)` pairs
+    ///
+    /// Only the points that are referenced are touched, so this is cheap for sparse inputs.
+    pub fn msm_sparse<'a>(&self, entries: impl Iterator<Item = (usize, &'a blstrs::Scalar)>) -> blstrs::G1Projective {
+        let entries: Vec<_> = entries
+            .map(|(index, scalar)| {
+                assert!(index < self.num_points, "index {index} is out of range for the table");
+                (index, scalar_to_limbs(scalar))
+            })
+            .collect();
+        let max_bits = entries.iter().map(|(_, limbs)| num_bits(limbs)).max().unwrap_or(0);
+        if max_bits == 0 {
+            return blstrs::G1Projective::identity();
+        }
+        // Small scalars only need the lower windows
+        let num_windows = (max_bits + 1).div_ceil(self.window_bits).min(self.num_windows);
+
+        let mut additions = Vec::new();
+        for (index, limbs) in &entries {
+            let digits = signed_digits(limbs, self.window_bits, num_windows);
+            let multiples = &self.multiples[index * self.num_windows..];
+            for (digit, multiple) in digits.into_iter().zip(multiples) {
+                if digit == 0 || bool::from(multiple.is_identity()) {
+                    continue;
+                }
+
+                let bucket = digit.unsigned_abs() as usize - 1;
+                additions.push((bucket, if digit > 0 { *multiple } else { -multiple }));
+            }
+        }
+
+        let buckets = accumulate_buckets(1 << (self.window_bits - 1), &additions);
+        sum_buckets(&buckets)
+    }
+
+    // One extra bit for the carry out of the most significant window
+    fn num_windows(window_bits: usize) -> usize {
+        (SCALAR_NUM_BITS + 1).div_ceil(window_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+
+    use super::*;
+
+    #[test]
+    fn table_msm_matches_naive() {
+        let num_points = 40;
+        let points: Vec<blstrs::G1Affine> = (0..num_points)
+            .map(|_| blstrs::G1Projective::random(&mut rand::thread_rng()).into())
+            .collect();
+        let mut scalars: Vec<_> = (0..num_points).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect();
+        scalars[0] = -blstrs::Scalar::one();
+        scalars[1] = blstrs::Scalar::zero();
+
+        let expected: blstrs::G1Projective = points.iter().zip(&scalars).map(|(point, scalar)| point * scalar).sum();
+
+        for window_bits in [2, 5, 8, 13] {
+            let table = FixedBaseTable::new(&points, window_bits);
+            assert_eq!(table.msm(&scalars), expected);
+            assert_eq!(table.memory_footprint(), FixedBaseTable::estimate_memory_footprint(num_points, window_bits));
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use alloc::{string::String, vec, vec::Vec};
+
+/// Errors that can occur when decoding a 0x prefixed hex string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexError {
+    /// The string does not start with `0x`
+    MissingPrefix,
+    /// The string does not have two hex digits for each of the expected bytes
+    InvalidLength { expected: usize, received: usize },
+    /// The character at this index of the string is not a hex digit
+    InvalidCharacter { index: usize },
+}
+
+impl core::fmt::Display for HexError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HexError::MissingPrefix => write!(f, "hex string does not start with 0x"),
+            HexError::InvalidLength { expected, received } => {
+                write!(f, "expected {expected} hex digits after 0x, but received {received}")
+            }
+            HexError::InvalidCharacter { index } => write!(f, "character {index} is not a hex digit"),
+        }
+    }
+}
+
+impl core::error::Error for HexError {}
+
+/// Encodes bytes as a lower case hex string with a `0x` prefix
+pub fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut string = String::with_capacity(2 + 2 * bytes.len());
+    string.push_str("0x");
+    for byte in bytes {
+        string.push(DIGITS[(byte >> 4) as usize] as char);
+        string.push(DIGITS[(byte & 0xf) as usize] as char);
+    }
+    string
+}
+
+/// Decodes a hex string with a `0x` prefix and exactly two digits per byte into `N` bytes
+///
+/// Upper and lower case digits are accepted, but the prefix must be a lower case `0x`.
+pub fn decode_hex_array<const N: usize>(string: &str) -> Result<[u8; N], HexError> {
+    let mut bytes = [0u8; N];
+    decode_hex_into(string, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Decodes a hex string with a `0x` prefix into exactly `length` bytes, see `decode_hex_array`
+pub fn decode_hex(string: &str, length: usize) -> Result<Vec<u8>, HexError> {
+    let mut bytes = vec![0u8; length];
+    decode_hex_into(string, &mut bytes)?;
+    Ok(bytes)
+}
+
+fn decode_hex_into(string: &str, bytes: &mut [u8]) -> Result<(), HexError> {
+    let digits = string.strip_prefix("0x").ok_or(HexError::MissingPrefix)?.as_bytes();
+    if digits.len() != 2 * bytes.len() {
+        return Err(HexError::InvalidLength { expected: 2 * bytes.len(), received: digits.len() });
+    }
+
+    let digit = |index: usize| -> Result<u8, HexError> {
+        match digits[index] {
+            digit @ b'0'..=b'9' => Ok(digit - b'0'),
+            digit @ b'a'..=b'f' => Ok(digit - b'a' + 10),
+            digit @ b'A'..=b'F' => Ok(digit - b'A' + 10),
+            // The index in the string, including the prefix
+            _ => Err(HexError::InvalidCharacter { index: index + 2 }),
+        }
+    };
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte = (digit(2 * index)? << 4) | digit(2 * index + 1)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_hex() {
+        assert_eq!(encode_hex(&[0x01, 0xab, 0xff]), "0x01abff");
+        assert_eq!(decode_hex_array::<3>("0x01abff"), Ok([0x01, 0xab, 0xff]));
+        assert_eq!(decode_hex_array::<3>("0x01ABFF"), Ok([0x01, 0xab, 0xff]));
+        assert_eq!(decode_hex("0x", 0), Ok(vec![]));
+
+        assert_eq!(decode_hex_array::<3>("01abff"), Err(HexError::MissingPrefix));
+        assert_eq!(decode_hex_array::<3>("0X01abff"), Err(HexError::MissingPrefix));
+        assert_eq!(decode_hex_array::<3>("0x1abff"), Err(HexError::InvalidLength { expected: 6, received: 5 }));
+        assert_eq!(decode_hex_array::<3>("0x01abff00"), Err(HexError::InvalidLength { expected: 6, received: 8 }));
+        assert_eq!(decode_hex_array::<3>("0x01agff"), Err(HexError::InvalidCharacter { index: 5 }));
+        assert_eq!(decode_hex_array::<1>("0x+1"), Err(HexError::InvalidCharacter { index: 2 }));
+    }
+}
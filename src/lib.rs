@@ -1,15 +1,65 @@
+// Without the default `prover` feature only the verifier side is built: the opening key, proofs,
+// `verifier` and the encodings.
+//
+// Without the default `std` feature the crate only needs `alloc`. The registries that cache
+// domains and commit keys, and encoding payloads from an `std::io::Read`, need `std`.
+// Note: blstrs still links `std`, which wasm32-unknown-unknown provides, so targets without
+// any `std` also need a no_std build of the curve backend.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "prover")]
+pub mod byte_range;
+#[cfg(feature = "prover")]
+pub mod cells;
 pub mod domain;
+pub mod eip2537;
+pub mod encoding;
+#[cfg(feature = "serde")]
+pub mod engine;
+#[cfg(feature = "prover")]
+pub mod fixed_base;
+pub mod hex;
+pub mod msm;
+#[cfg(feature = "prover")]
+pub mod multi_proof;
+#[cfg(feature = "prover")]
 pub mod commit_key;
+#[cfg(feature = "prover")]
+pub mod context;
+#[cfg(feature = "prover")]
+pub mod danksharding;
 pub mod opening_key;
 pub mod polynomial;
 pub mod proof;
+#[cfg(feature = "prover")]
+pub mod sampling;
+#[cfg(feature = "prover")]
 pub mod params;
+#[cfg(feature = "serde")]
+mod serde_impls;
+pub mod serialization;
+#[cfg(feature = "prover")]
+pub mod simulation;
+pub mod ssz;
+#[cfg(all(test, feature = "prover"))]
+mod test_utils;
 pub mod utils;
+pub mod verifier;
+
+#[cfg(feature = "embedded-setup")]
+pub mod embedded_setup;
 
+// The number of field elements in a mainnet blob
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+// The number of bytes in a serialized mainnet blob
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * SCALAR_SERIALIZED_SIZE;
 // The number of bytes needed to represent a scalar
 pub const SCALAR_SERIALIZED_SIZE: usize = 32;
 // The number of bytes needed to represent a compressed G1 point
 pub const G1_POINT_SERIALIZED_SIZE: usize = 48;
 // The number of bytes needed to represent a compressed G2 point
 pub const G2_POINT_SERIALIZED_SIZE: usize = 96;
+// The number of bytes needed to represent an opening key: the G1 generator, the G2 generator and \tau G2
+pub const OPENING_KEY_SERIALIZED_SIZE: usize = G1_POINT_SERIALIZED_SIZE + 2 * G2_POINT_SERIALIZED_SIZE;
@@ -0,0 +1,354 @@
+use alloc::{vec, vec::Vec};
+
+use ff::Field;
+use group::{prime::PrimeCurveAffine, Group};
+
+// Below this many points, the bucket method does not pay for its setup cost
+const NAIVE_THRESHOLD: usize = 16;
+
+/// A multi-scalar multiplication over affine points, using Pippenger's bucket method
+///
+/// - Scalars are recoded into signed digits, so each window needs `2^(c-1)` buckets instead of `2^c`.
+/// - Points are added into the buckets in affine form, with the inversions for all additions
+///   in a round shared through a batch inversion.
+/// - The window size `c` is chosen from the number of points, and only the windows that are
+///   needed for the largest scalar are processed, so small scalars are cheaper.
+///
+/// Note: For full size random scalars, blst's Pippenger (used by `g1_lincomb`) is still faster,
+/// see `benches/msm.rs`. The building blocks here are shared with the fixed-base commit paths.
+pub fn msm(points: &[blstrs::G1Affine], scalars: &[blstrs::Scalar]) -> blstrs::G1Projective {
+    assert_eq!(points.len(), scalars.len());
+
+    let scalars: Vec<_> = scalars.iter().map(scalar_to_limbs).collect();
+    let max_bits = scalars.iter().map(num_bits).max().unwrap_or(0);
+    if max_bits == 0 {
+        return blstrs::G1Projective::identity();
+    }
+
+    if points.len() < NAIVE_THRESHOLD {
+        return points
+            .iter()
+            .zip(&scalars)
+            .map(|(point, limbs)| point * limbs_to_scalar(limbs))
+            .sum();
+    }
+
+    let window_bits = window_size(points.len());
+    // One extra bit for the carry out of the most significant window
+    let num_windows = (max_bits + 1).div_ceil(window_bits);
+    let digits: Vec<_> = scalars.iter().map(|limbs| signed_digits(limbs, window_bits, num_windows)).collect();
+
+    let num_buckets = 1 << (window_bits - 1);
+    let mut result = blstrs::G1Projective::identity();
+    for window in (0..num_windows).rev() {
+        for _ in 0..window_bits {
+            result = result.double();
+        }
+
+        let additions: Vec<_> = points
+            .iter()
+            .zip(&digits)
+            .filter(|(point, digits)| digits[window] != 0 && !bool::from(point.is_identity()))
+            .map(|(point, digits)| {
+                let digit = digits[window];
+                let bucket = digit.unsigned_abs() as usize - 1;
+                if digit > 0 {
+                    (bucket, *point)
+                } else {
+                    (bucket, -point)
+                }
+            })
+            .collect();
+
+        let buckets = accumulate_buckets(num_buckets, &additions);
+        result += sum_buckets(&buckets);
+    }
+
+    result
+}
+
+// A multi-scalar multiplication, using blst's Pippenger directly on the affine points
+//
+// `G1Projective::multi_exp` takes projective points and normalizes them back to affine form
+// on every call, which is wasted work for keys that are already affine.
+pub fn g1_lincomb(points: &[blstrs::G1Affine], scalars: &[blstrs::Scalar]) -> blstrs::G1Affine {
+    // TODO: Spec says we should panic, but as a lib its better to return result
+    assert_eq!(points.len(), scalars.len());
+
+    blst_msm(&to_blst_affines(points), scalars).into()
+}
+
+// The affine points in the form that blst's Pippenger takes them, this is only a copy
+pub(crate) fn to_blst_affines(points: &[blstrs::G1Affine]) -> Vec<blst::blst_p1_affine> {
+    points.iter().map(|point| *point.as_ref()).collect()
+}
+
+// blst's Pippenger over points from `to_blst_affines`, it splits the work over blst's own thread pool
+pub(crate) fn blst_msm(points: &[blst::blst_p1_affine], scalars: &[blstrs::Scalar]) -> blstrs::G1Projective {
+    assert_eq!(points.len(), scalars.len());
+    let mut result = blstrs::G1Projective::identity();
+    if points.is_empty() {
+        return result;
+    }
+
+    let scalars: Vec<u8> = scalars.iter().flat_map(|scalar| scalar.to_bytes_le()).collect();
+    *result.as_mut() = blst::MultiPoint::mult(points, &scalars, 255);
+    result
+}
+
+// Chooses the window size for the given number of points
+//
+// This is roughly ln(num_points) + 2, which balances the cost of
+// filling the buckets against the cost of summing them up.
+pub(crate) fn window_size(num_points: usize) -> usize {
+    if num_points < 32 {
+        3
+    } else {
+        (num_points.ilog2() as usize * 69 / 100) + 2
+    }
+}
+
+// Adds each point into its bucket, using affine additions
+//
+// The points are grouped by bucket, then every round adds up adjacent pairs of points
+// within each bucket. All of the additions in a round are independent, so they share
+// one batch inversion, and the number of rounds is logarithmic in the largest bucket.
+pub(crate) fn accumulate_buckets(num_buckets: usize, additions: &[(usize, blstrs::G1Affine)]) -> Vec<Option<blstrs::G1Affine>> {
+    // Counting sort by bucket index, so that each bucket is a contiguous segment
+    let mut lengths = vec![0usize; num_buckets];
+    for (bucket, _) in additions {
+        lengths[*bucket] += 1;
+    }
+    let mut starts = Vec::with_capacity(num_buckets);
+    let mut offset = 0;
+    for length in &lengths {
+        starts.push(offset);
+        offset += length;
+    }
+    let mut points = vec![blstrs::G1Affine::identity(); additions.len()];
+    let mut next = starts.clone();
+    for (bucket, point) in additions {
+        points[next[*bucket]] = *point;
+        next[*bucket] += 1;
+    }
+
+    let mut pairs = Vec::new();
+    let mut denominators = Vec::new();
+    loop {
+        pairs.clear();
+        for (start, length) in starts.iter().zip(&lengths) {
+            pairs.extend((0..length / 2).map(|k| start + 2 * k));
+        }
+        if pairs.is_empty() {
+            break;
+        }
+
+        // The denominator of the slope, this is zero when adding a point to its negation
+        denominators.clear();
+        denominators.extend(pairs.iter().map(|&index| {
+            let (lhs, rhs) = (points[index], points[index + 1]);
+            if lhs.x() == rhs.x() {
+                if lhs.y() == rhs.y() {
+                    lhs.y().double()
+                } else {
+                    Field::zero()
+                }
+            } else {
+                rhs.x() - lhs.x()
+            }
+        }));
+        batch_inversion(&mut denominators);
+
+        // Store the sum of each pair in place of its left element
+        for (&index, inverse) in pairs.iter().zip(&denominators) {
+            let (lhs, rhs) = (points[index], points[index + 1]);
+            points[index] = if bool::from(inverse.is_zero()) {
+                blstrs::G1Affine::identity()
+            } else {
+                let lambda = if lhs.x() == rhs.x() {
+                    lhs.x().square().mul3() * inverse
+                } else {
+                    (rhs.y() - lhs.y()) * inverse
+                };
+                let x = lambda.square() - lhs.x() - rhs.x();
+                let y = lambda * (lhs.x() - x) - lhs.y();
+                blstrs::G1Affine::from_raw_unchecked(x, y, false)
+            };
+        }
+
+        // Compact each bucket, dropping sums that were the identity
+        for (start, length) in starts.iter().zip(lengths.iter_mut()) {
+            let mut new_length = 0;
+            for k in (0..*length).step_by(2) {
+                let point = points[start + k];
+                if !bool::from(point.is_identity()) {
+                    points[start + new_length] = point;
+                    new_length += 1;
+                }
+            }
+            *length = new_length;
+        }
+    }
+
+    starts
+        .iter()
+        .zip(&lengths)
+        .map(|(start, length)| (*length == 1).then(|| points[*start]))
+        .collect()
+}
+
+// Computes \sum (i + 1) * buckets[i] with a running sum
+pub(crate) fn sum_buckets(buckets: &[Option<blstrs::G1Affine>]) -> blstrs::G1Projective {
+    let mut running_sum = blstrs::G1Projective::identity();
+    let mut result = blstrs::G1Projective::identity();
+    for bucket in buckets.iter().rev() {
+        if let Some(point) = bucket {
+            running_sum += point;
+        }
+        result += running_sum;
+    }
+    result
+}
+
+// Recodes a scalar into signed digits in the range [-2^(c-1), 2^(c-1)]
+//
+// Digits above 2^(c-1) are replaced by `digit - 2^c`, carrying one into the next window.
+pub(crate) fn signed_digits(limbs: &[u64; 4], window_bits: usize, num_windows: usize) -> Vec<i64> {
+    let radix = 1i64 << window_bits;
+    let half_radix = radix / 2;
+
+    let mut carry = 0;
+    (0..num_windows)
+        .map(|window| {
+            let digit = extract_bits(limbs, window * window_bits, window_bits) as i64 + carry;
+            if digit > half_radix {
+                carry = 1;
+                digit - radix
+            } else {
+                carry = 0;
+                digit
+            }
+        })
+        .collect()
+}
+
+// Returns `num_bits` bits of the little endian limbs, starting at bit `offset`
+fn extract_bits(limbs: &[u64; 4], offset: usize, num_bits: usize) -> u64 {
+    let limb = offset / 64;
+    if limb >= limbs.len() {
+        return 0;
+    }
+
+    let shift = offset % 64;
+    let mut bits = limbs[limb] >> shift;
+    if shift + num_bits > 64 && limb + 1 < limbs.len() {
+        bits |= limbs[limb + 1] << (64 - shift);
+    }
+    bits & ((1 << num_bits) - 1)
+}
+
+pub(crate) fn scalar_to_limbs(scalar: &blstrs::Scalar) -> [u64; 4] {
+    let bytes = scalar.to_bytes_le();
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+        *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    limbs
+}
+
+fn limbs_to_scalar(limbs: &[u64; 4]) -> blstrs::Scalar {
+    blstrs::Scalar::from_u64s_le(limbs).unwrap()
+}
+
+pub(crate) fn num_bits(limbs: &[u64; 4]) -> usize {
+    limbs
+        .iter()
+        .rposition(|limb| *limb != 0)
+        .map(|index| index * 64 + (64 - limbs[index].leading_zeros() as usize))
+        .unwrap_or(0)
+}
+
+// Montgomery's trick, see `utils::serial_batch_inversion`
+//
+// This is generic because blstrs does not export its base field type.
+// Zero elements are left as zero.
+fn batch_inversion<F: Field>(v: &mut [F]) {
+    let mut prod = Vec::with_capacity(v.len());
+    let mut acc = F::one();
+    for f in v.iter().filter(|f| !bool::from(f.is_zero())) {
+        prod.push(acc);
+        acc *= f;
+    }
+
+    let mut acc = acc.invert().unwrap();
+    for (f, s) in v.iter_mut().rev().filter(|f| !bool::from(f.is_zero())).zip(prod.into_iter().rev()) {
+        let inverse = acc * s;
+        acc *= *f;
+        *f = inverse;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_msm(points: &[blstrs::G1Affine], scalars: &[blstrs::Scalar]) -> blstrs::G1Projective {
+        points.iter().zip(scalars).map(|(point, scalar)| point * scalar).sum()
+    }
+
+    fn random_points(num_points: usize) -> Vec<blstrs::G1Affine> {
+        (0..num_points)
+            .map(|_| blstrs::G1Projective::random(&mut rand::thread_rng()).into())
+            .collect()
+    }
+
+    #[test]
+    fn msm_matches_naive() {
+        for num_points in [1, 7, 16, 33, 100, 300] {
+            let points = random_points(num_points);
+            let scalars: Vec<_> = (0..num_points).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect();
+            assert_eq!(msm(&points, &scalars), naive_msm(&points, &scalars));
+        }
+    }
+
+    #[test]
+    fn msm_edge_cases() {
+        let num_points = 64;
+        let point: blstrs::G1Affine = blstrs::G1Projective::random(&mut rand::thread_rng()).into();
+
+        // Repeated points force doublings, a point and its negation cancel out,
+        // and the identity point must be skipped
+        let mut points = vec![point; num_points];
+        points[1] = -point;
+        points[2] = blstrs::G1Affine::identity();
+
+        let mut scalars: Vec<_> = (0..num_points as u64).map(blstrs::Scalar::from).collect();
+        scalars[3] = -blstrs::Scalar::one();
+        scalars[4] = blstrs::Scalar::zero();
+        assert_eq!(msm(&points, &scalars), naive_msm(&points, &scalars));
+
+        // Small scalars only use the lower windows
+        let points = random_points(num_points);
+        let scalars: Vec<_> = (0..num_points as u64).map(|i| blstrs::Scalar::from(i % 256)).collect();
+        assert_eq!(msm(&points, &scalars), naive_msm(&points, &scalars));
+
+        assert_eq!(msm(&points, &vec![blstrs::Scalar::zero(); num_points]), blstrs::G1Projective::identity());
+    }
+
+    #[test]
+    fn signed_digits_recompose() {
+        let scalar = -blstrs::Scalar::from(12345u64);
+        let limbs = scalar_to_limbs(&scalar);
+        for window_bits in [3, 8, 10, 13] {
+            let num_windows = (num_bits(&limbs) + 1).div_ceil(window_bits);
+            let digits = signed_digits(&limbs, window_bits, num_windows);
+
+            let radix = blstrs::Scalar::from(1u64 << window_bits);
+            let recomposed = digits.iter().rev().fold(blstrs::Scalar::zero(), |acc, digit| {
+                let digit_scalar = blstrs::Scalar::from(digit.unsigned_abs());
+                acc * radix + if *digit < 0 { -digit_scalar } else { digit_scalar }
+            });
+            assert_eq!(recomposed, scalar);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use alloc::{vec, vec::Vec};
+
+use ff::Field;
+use pairing_lib::{group::Group, MillerLoopResult, MultiMillerLoop};
+
+use crate::{
+    commit_key::g1_lincomb, domain::Domain, params::PublicParameters, polynomial::Polynomial,
+    utils::serial_batch_inversion,
+};
+
+/// A proof that a committed polynomial takes the given values at several elements of the domain
+///
+/// For the points `x_i` with vanishing polynomial `Z(X) = \prod (X - x_i)`, and the polynomial `I(X)`
+/// that interpolates the values at those points, the proof is a commitment to `(p(X) - I(X)) / Z(X)`.
+pub struct MultiPointProof {
+    // Commitment to the polynomial that we have created a KZG proof for.
+    pub polynomial_commitment: blstrs::G1Affine,
+
+    // Commitment to the quotient polynomial
+    pub quotient_commitment: blstrs::G1Affine,
+
+    // The evaluations at the opened domain elements, in the order of the indices
+    pub output_points: Vec<blstrs::Scalar>,
+}
+
+impl MultiPointProof {
+    /// Creates a proof for the evaluations of `poly` at the domain elements with the given indices
+    ///
+    /// The quotient is computed in monomial form and committed to with the monomial key, so this
+    /// costs one inverse FFT and a division that is linear in the number of indices.
+    ///
+    /// Panics, if the indices are not distinct elements of the domain
+    pub fn create(
+        public_parameters: &PublicParameters,
+        poly: &Polynomial,
+        poly_comm: blstrs::G1Affine,
+        indices: &[usize],
+        domain: &Domain,
+    ) -> MultiPointProof {
+        let points = opening_points(indices, domain);
+        let output_points: Vec<_> = indices.iter().map(|index| poly[*index]).collect();
+
+        let vanishing_polynomial = polynomial_from_roots(&points);
+        let interpolation = interpolate(&points, &output_points);
+
+        let mut numerator = poly.to_coefficients();
+        for (coefficient, interpolation_coefficient) in numerator.iter_mut().zip(&interpolation) {
+            *coefficient -= interpolation_coefficient;
+        }
+        let quotient = divide_by_monic(numerator, &vanishing_polynomial);
+
+        let monomial_points = public_parameters.commit_key_monomial.points();
+        let quotient_commitment = g1_lincomb(&monomial_points[..quotient.len()], &quotient);
+
+        MultiPointProof { polynomial_commitment: poly_comm, quotient_commitment, output_points }
+    }
+
+    /// Verifies the proof for the domain elements with the given indices
+    ///
+    /// The check is e(C - I(\tau) * G1, G2) = e(\pi, Z(\tau) * G2), which needs the first `k + 1`
+    /// powers of \tau in G2 for `k` indices.
+    pub fn verify(&self, indices: &[usize], public_parameters: &PublicParameters, domain: &Domain) -> bool {
+        if indices.len() != self.output_points.len()
+            || public_parameters.g2_monomial.len() <= indices.len()
+            || indices.iter().any(|index| *index >= domain.size())
+        {
+            return false;
+        }
+        let mut sorted_indices = indices.to_vec();
+        sorted_indices.sort_unstable();
+        if sorted_indices.windows(2).any(|pair| pair[0] == pair[1]) {
+            return false;
+        }
+
+        let points = opening_points(indices, domain);
+        let vanishing_polynomial = polynomial_from_roots(&points);
+        let interpolation = interpolate(&points, &self.output_points);
+
+        let monomial_points = public_parameters.commit_key_monomial.points();
+        let interpolation_commitment = g1_lincomb(&monomial_points[..interpolation.len()], &interpolation);
+        let vanishing_commitment: blstrs::G2Affine = public_parameters
+            .g2_monomial
+            .iter()
+            .zip(&vanishing_polynomial)
+            .map(|(point, coefficient)| point * coefficient)
+            .sum::<blstrs::G2Projective>()
+            .into();
+
+        let lhs: blstrs::G1Affine = (blstrs::G1Projective::from(self.polynomial_commitment) - interpolation_commitment).into();
+        let neg_quotient_commitment = -self.quotient_commitment;
+        let prepared_vanishing = blstrs::G2Prepared::from(vanishing_commitment);
+
+        let terms = [(&lhs, &public_parameters.opening_key.prepared_g2), (&neg_quotient_commitment, &prepared_vanishing)];
+        let pairing = blstrs::Bls12::multi_miller_loop(&terms).final_exponentiation();
+
+        pairing.is_identity().into()
+    }
+}
+
+fn opening_points(indices: &[usize], domain: &Domain) -> Vec<blstrs::Scalar> {
+    indices.iter().map(|index| domain[*index]).collect()
+}
+
+// Returns the coefficients of \prod (X - r_i), which has degree `roots.len()`
+fn polynomial_from_roots(roots: &[blstrs::Scalar]) -> Vec<blstrs::Scalar> {
+    let mut coefficients = vec![blstrs::Scalar::one()];
+    for root in roots {
+        coefficients.insert(0, blstrs::Scalar::zero());
+        for index in 0..coefficients.len() - 1 {
+            let next = coefficients[index + 1];
+            coefficients[index] -= next * root;
+        }
+    }
+    coefficients
+}
+
+// Returns the coefficients of the polynomial of degree less than `points.len()` with `I(x_i) = y_i`
+//
+// With Z(X) = \prod (X - x_i), this is I(X) = \sum y_i / Z'(x_i) * Z(X) / (X - x_i)
+fn interpolate(points: &[blstrs::Scalar], values: &[blstrs::Scalar]) -> Vec<blstrs::Scalar> {
+    let vanishing_polynomial = polynomial_from_roots(points);
+
+    // Z'(x_i) = \prod_{j != i} (x_i - x_j)
+    let mut weights: Vec<_> = points
+        .iter()
+        .enumerate()
+        .map(|(i, x_i)| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, x_j)| x_i - x_j)
+                .product::<blstrs::Scalar>()
+        })
+        .collect();
+    serial_batch_inversion(&mut weights);
+
+    let mut interpolation = vec![blstrs::Scalar::zero(); points.len()];
+    for ((point, value), weight) in points.iter().zip(values).zip(weights) {
+        let scale = *value * weight;
+        let basis = divide_by_monic(vanishing_polynomial.clone(), &[-point, blstrs::Scalar::one()]);
+        for (coefficient, basis_coefficient) in interpolation.iter_mut().zip(basis) {
+            *coefficient += scale * basis_coefficient;
+        }
+    }
+    interpolation
+}
+
+// Divides `numerator` by the monic polynomial `divisor`, and returns the quotient
+//
+// The remainder must be zero, which is checked in debug builds.
+fn divide_by_monic(mut numerator: Vec<blstrs::Scalar>, divisor: &[blstrs::Scalar]) -> Vec<blstrs::Scalar> {
+    let divisor_degree = divisor.len() - 1;
+    if numerator.len() <= divisor_degree {
+        debug_assert!(numerator.iter().all(|coefficient| bool::from(coefficient.is_zero())));
+        return Vec::new();
+    }
+
+    let mut quotient = vec![blstrs::Scalar::zero(); numerator.len() - divisor_degree];
+    for index in (0..quotient.len()).rev() {
+        let leading = numerator[index + divisor_degree];
+        quotient[index] = leading;
+        for (offset, divisor_coefficient) in divisor.iter().enumerate() {
+            numerator[index + offset] -= leading * divisor_coefficient;
+        }
+    }
+    debug_assert!(numerator.iter().all(|coefficient| bool::from(coefficient.is_zero())));
+    quotient
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_vector(length: usize) -> Vec<blstrs::Scalar> {
+        (0..length).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect()
+    }
+
+    #[test]
+    fn multi_point_proof_smoke() {
+        let domain = Domain::new(16);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+        let poly = Polynomial::new(random_vector(16));
+        let poly_comm = public_parameters.commit_key.commit(&poly);
+
+        for indices in [vec![3], vec![0, 1, 2, 3, 4], vec![15, 2, 9]] {
+            let proof = MultiPointProof::create(&public_parameters, &poly, poly_comm, &indices, &domain);
+            assert!(proof.verify(&indices, &public_parameters, &domain));
+
+            let mut wrong_indices = indices.clone();
+            wrong_indices[0] = (wrong_indices[0] + 5) % 16;
+            assert!(!proof.verify(&wrong_indices, &public_parameters, &domain));
+        }
+
+        let indices = [4, 5, 6];
+        let mut proof = MultiPointProof::create(&public_parameters, &poly, poly_comm, &indices, &domain);
+        proof.output_points[1] += blstrs::Scalar::one();
+        assert!(!proof.verify(&indices, &public_parameters, &domain));
+        assert!(!proof.verify(&[4, 5, 5], &public_parameters, &domain));
+    }
+
+    #[test]
+    fn interpolation_matches_values() {
+        let points = random_vector(6);
+        let values = random_vector(6);
+        let interpolation = interpolate(&points, &values);
+        for (point, value) in points.iter().zip(&values) {
+            let evaluation = interpolation.iter().rev().fold(blstrs::Scalar::zero(), |acc, coefficient| acc * point + coefficient);
+            assert_eq!(evaluation, *value);
+        }
+    }
+}
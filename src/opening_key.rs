@@ -1,7 +1,31 @@
+use alloc::vec::Vec;
+
 
 use pairing_lib::{group::Group, MillerLoopResult, MultiMillerLoop};
 use blstrs::{Bls12, G2Prepared};
 
+use crate::{msm::g1_lincomb, G1_POINT_SERIALIZED_SIZE, G2_POINT_SERIALIZED_SIZE, OPENING_KEY_SERIALIZED_SIZE};
+
+/// Errors that can occur when deserializing an opening key, naming the point that is invalid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpeningKeyDecodingError {
+    InvalidG1Generator,
+    InvalidG2Generator,
+    InvalidTauG2,
+}
+
+impl core::fmt::Display for OpeningKeyDecodingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            OpeningKeyDecodingError::InvalidG1Generator => write!(f, "G1 generator is not a valid compressed G1 point"),
+            OpeningKeyDecodingError::InvalidG2Generator => write!(f, "G2 generator is not a valid compressed G2 point"),
+            OpeningKeyDecodingError::InvalidTauG2 => write!(f, "tau times the G2 generator is not a valid compressed G2 point"),
+        }
+    }
+}
+
+impl core::error::Error for OpeningKeyDecodingError {}
+
 /// Opening Key is used to verify opening proofs made about a committed polynomial.
 #[derive(Clone, Debug)]
 pub struct OpeningKey {
@@ -25,6 +49,32 @@ impl OpeningKey {
         OpeningKey { g1_gen, g2_gen, tau_g2_gen, prepared_g2, prepared_beta_g2, }
     }
 
+    /// Serializes the key as the compressed G1 generator, G2 generator and \tau G2
+    ///
+    /// This is all that a verifier needs from the trusted setup, see `verifier::mainnet_opening_key`.
+    pub fn to_bytes(&self) -> [u8; OPENING_KEY_SERIALIZED_SIZE] {
+        let mut bytes = [0; OPENING_KEY_SERIALIZED_SIZE];
+        let (g1_gen, g2_points) = bytes.split_at_mut(G1_POINT_SERIALIZED_SIZE);
+        let (g2_gen, tau_g2_gen) = g2_points.split_at_mut(G2_POINT_SERIALIZED_SIZE);
+        g1_gen.copy_from_slice(&self.g1_gen.to_compressed());
+        g2_gen.copy_from_slice(&self.g2_gen.to_compressed());
+        tau_g2_gen.copy_from_slice(&self.tau_g2_gen.to_compressed());
+        bytes
+    }
+
+    /// Deserializes a key from `to_bytes`, checking that every point is in its prime order subgroup
+    pub fn from_bytes(bytes: &[u8; OPENING_KEY_SERIALIZED_SIZE]) -> Result<OpeningKey, OpeningKeyDecodingError> {
+        let (g1_gen, g2_points) = bytes.split_at(G1_POINT_SERIALIZED_SIZE);
+        let (g2_gen, tau_g2_gen) = g2_points.split_at(G2_POINT_SERIALIZED_SIZE);
+        let g1_gen = Option::from(blstrs::G1Affine::from_compressed(g1_gen.try_into().unwrap()))
+            .ok_or(OpeningKeyDecodingError::InvalidG1Generator)?;
+        let g2_gen = Option::from(blstrs::G2Affine::from_compressed(g2_gen.try_into().unwrap()))
+            .ok_or(OpeningKeyDecodingError::InvalidG2Generator)?;
+        let tau_g2_gen = Option::from(blstrs::G2Affine::from_compressed(tau_g2_gen.try_into().unwrap()))
+            .ok_or(OpeningKeyDecodingError::InvalidTauG2)?;
+        Ok(OpeningKey::new(g1_gen, g2_gen, tau_g2_gen))
+    }
+
     /// Checks that a polynomial `p` was evaluated at a point `z` and returned the value specified `y`.
     /// ie. y = p(z).
     pub fn verify(
@@ -34,13 +84,70 @@ impl OpeningKey {
         poly_comm: blstrs::G1Affine,
         witness_comm: blstrs::G1Affine,
     ) -> bool {
-        // TODO: .into is doing an inversion. Check if batch normalization saves anything here
-        // codepath : G1Projective::batch_normalize(p, q)
-        let inner_a: blstrs::G1Affine = (poly_comm - (self.g1_gen * output_point)).into();
-        let inner_b: blstrs::G2Affine = (self.tau_g2_gen - (self.g2_gen * input_point)).into();
-        let prepared_inner_b = G2Prepared::from(-inner_b);
+        // The check is e(C - y * G1, G2) = e(\pi, \tau * G2 - z * G2).
+        // We move `z` into G1 instead, since G1 arithmetic is cheaper and it lets us use
+        // the prepared G2 elements instead of preparing a new one for every proof:
+        //  e(C - y * G1 + z * \pi, G2) = e(\pi, \tau * G2)
+        let inner_a: blstrs::G1Affine = (poly_comm - (self.g1_gen * output_point) + (witness_comm * input_point)).into();
+        let neg_witness_comm = -witness_comm;
+
+        let terms = [(&inner_a, &self.prepared_g2), (&neg_witness_comm, &self.prepared_beta_g2)];
+        let pairing = Bls12::multi_miller_loop(&terms).final_exponentiation();
+
+        pairing.is_identity().into()
+    }
+
+    /// Checks many proofs at once, each that `p_i(z_i) = y_i`
+    ///
+    /// The checks are combined with the powers of `challenge`, which must be random or derived from
+    /// all of the inputs by Fiat-Shamir:
+    ///  e(\sum r^i (C_i - y_i * G1 + z_i * \pi_i), G2) = e(\sum r^i \pi_i, \tau * G2)
+    ///
+    /// Panics, if the inputs do not have the same length
+    pub fn verify_batch(
+        &self,
+        input_points: &[blstrs::Scalar],
+        output_points: &[blstrs::Scalar],
+        poly_comms: &[blstrs::G1Affine],
+        witness_comms: &[blstrs::G1Affine],
+        challenge: blstrs::Scalar,
+    ) -> bool {
+        let num_proofs = input_points.len();
+        assert!(
+            output_points.len() == num_proofs && poly_comms.len() == num_proofs && witness_comms.len() == num_proofs,
+            "inputs must have the same length"
+        );
+        if num_proofs == 0 {
+            return true;
+        }
+
+        let mut powers = Vec::with_capacity(num_proofs);
+        let mut power = blstrs::Scalar::from(1u64);
+        for _ in 0..num_proofs {
+            powers.push(power);
+            power *= challenge;
+        }
+
+        // \sum r^i * C_i + r^i * z_i * \pi_i - (\sum r^i * y_i) * G1
+        let mut points = Vec::with_capacity(2 * num_proofs + 1);
+        let mut scalars = Vec::with_capacity(2 * num_proofs + 1);
+        let mut sum_of_outputs = blstrs::Scalar::from(0u64);
+        for (((power, input_point), output_point), (poly_comm, witness_comm)) in
+            powers.iter().zip(input_points).zip(output_points).zip(poly_comms.iter().zip(witness_comms))
+        {
+            points.push(*poly_comm);
+            scalars.push(*power);
+            points.push(*witness_comm);
+            scalars.push(power * input_point);
+            sum_of_outputs += power * output_point;
+        }
+        points.push(self.g1_gen);
+        scalars.push(-sum_of_outputs);
+
+        let inner_a = g1_lincomb(&points, &scalars);
+        let neg_witness_lincomb = -g1_lincomb(witness_comms, &powers);
 
-        let terms = [(&inner_a, &self.prepared_g2), (&witness_comm, &prepared_inner_b)];
+        let terms = [(&inner_a, &self.prepared_g2), (&neg_witness_lincomb, &self.prepared_beta_g2)];
         let pairing = Bls12::multi_miller_loop(&terms).final_exponentiation();
 
         pairing.is_identity().into()
@@ -1,14 +1,59 @@
+use alloc::{sync::Arc, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::{collections::HashMap, sync::{Mutex, OnceLock, PoisonError}};
+
+use pairing_lib::{group::Group, MillerLoopResult, MultiMillerLoop};
+use rand_core::RngCore;
+
 use crate::{commit_key::*, opening_key::OpeningKey, domain::Domain};
 
 // This is the SRS in lagrange form.
 //
 // The lagrange form is used to avoid the need to do an inverse fft to commit to polynomials.
+//
+// The SRS in monomial form is kept around, so that lagrange keys for smaller domains
+// can be derived from the same setup.
 pub struct PublicParameters {
+    pub commit_key_monomial: CommitKey,
     pub commit_key: CommitKeyLagrange,
     pub opening_key: OpeningKey,
+    // The powers of \tau in G2, `{ \tau^i G2 }`. Opening a single point only needs the first two,
+    // opening a coset of size `k` needs `\tau^k G2`.
+    pub g2_monomial: Vec<blstrs::G2Affine>,
+    // Lagrange keys that have been derived from `commit_key_monomial`, indexed by domain size.
+    // The map is only locked to find the cell for a size, so keys for different sizes
+    // are derived concurrently.
+    #[cfg(feature = "std")]
+    derived_lagrange_keys: Mutex<HashMap<usize, Arc<OnceLock<Arc<CommitKeyLagrange>>>>>,
 }
 
 impl PublicParameters {
+    pub fn new(commit_key_monomial: CommitKey, commit_key: CommitKeyLagrange, opening_key: OpeningKey) -> Self {
+        let g2_monomial = vec![opening_key.g2_gen, opening_key.tau_g2_gen];
+        PublicParameters {
+            commit_key_monomial,
+            commit_key,
+            opening_key,
+            g2_monomial,
+            #[cfg(feature = "std")]
+            derived_lagrange_keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replaces the G2 powers of \tau, which by default are only the two points in the opening key
+    ///
+    /// Panics, if the points do not start with the points in the opening key
+    pub fn with_g2_monomial(mut self, g2_monomial: Vec<blstrs::G2Affine>) -> Self {
+        assert!(
+            g2_monomial.len() >= 2
+                && g2_monomial[0] == self.opening_key.g2_gen
+                && g2_monomial[1] == self.opening_key.tau_g2_gen,
+            "G2 points do not match the opening key"
+        );
+        self.g2_monomial = g2_monomial;
+        self
+    }
+
     pub fn from_secret_insecure(tau: u64, domain: &Domain) -> Self {
         use ff::Field;
         use group::prime::PrimeCurveAffine;
@@ -18,15 +63,230 @@ impl PublicParameters {
         let g2_gen = blstrs::G2Affine::generator();
         let tau_g2_gen = (g2_gen * tau_fr).into();
 
+        let g2_monomial: Vec<blstrs::G2Affine> = (0..=domain.size())
+            .map(|index| (g2_gen * tau_fr.pow_vartime([index as u64])).into())
+            .collect();
+
         let powers_of_tau_g1: Vec<blstrs::G1Affine> = (0..domain.size())
             .map(|index| {
-                let secret_exp = tau_fr.pow_vartime(&[index as u64]);
+                let secret_exp = tau_fr.pow_vartime([index as u64]);
                 (blstrs::G1Affine::generator() * secret_exp).into()
             })
             .collect();
 
-        let commit_key = CommitKey::new(powers_of_tau_g1).into_lagrange(&domain);
+        let commit_key_monomial = CommitKey::new(powers_of_tau_g1);
+        let commit_key = commit_key_monomial.to_lagrange(domain);
         let opening_key = OpeningKey::new(g1_gen, g2_gen, tau_g2_gen);
-        PublicParameters { commit_key, opening_key }
+        PublicParameters::new(commit_key_monomial, commit_key, opening_key).with_g2_monomial(g2_monomial)
+    }
+
+    /// Returns the lagrange commit key for `domain`
+    ///
+    /// Keys are derived from the monomial key on first use and cached,
+    /// so this is cheap to call repeatedly for the same domain size.
+    /// Without the `std` feature the keys are not cached.
+    ///
+    /// Panics, if the domain is larger than the SRS
+    pub fn commit_key_for_domain(&self, domain: &Domain) -> Arc<CommitKeyLagrange> {
+        #[cfg(feature = "std")]
+        {
+            let cell = {
+                let mut derived_keys = self.derived_lagrange_keys.lock().unwrap_or_else(PoisonError::into_inner);
+                derived_keys.entry(domain.size()).or_default().clone()
+            };
+            cell.get_or_init(|| self.derive_commit_key(domain)).clone()
+        }
+        #[cfg(not(feature = "std"))]
+        self.derive_commit_key(domain)
+    }
+
+    fn derive_commit_key(&self, domain: &Domain) -> Arc<CommitKeyLagrange> {
+        if domain.size() == self.commit_key.points().len() {
+            Arc::new(self.commit_key.clone())
+        } else {
+            Arc::new(self.commit_key_monomial.to_lagrange(domain))
+        }
+    }
+
+    /// Checks that the commit keys and the opening key were derived from the same secret `\tau`
+    ///
+    /// The lagrange points are converted back to the monomial points `{ \tau^i G }`, which
+    /// must be a prefix of the monomial key. The monomial key must start at the G1 generator
+    /// of the opening key and be consecutive powers of the `\tau` in `tau_g2_gen`. The G2 points
+    /// must start at the two points of the opening key and also be consecutive powers of `\tau`.
+    ///
+    /// Note: This is a probabilistic check, `rng` must be a cryptographically secure rng.
+    pub fn verify_consistency(&self, mut rng: impl RngCore) -> bool {
+        let num_points = self.commit_key.points().len();
+        let monomial_points = self.commit_key_monomial.points();
+        if !num_points.is_power_of_two() || monomial_points.len() < num_points {
+            return false;
+        }
+
+        let domain = Domain::new(num_points);
+        let lagrange_as_monomial = domain.fft_g1(self.commit_key.points().to_vec());
+        if lagrange_as_monomial != monomial_points[..num_points] {
+            return false;
+        }
+        if monomial_points[0] != self.opening_key.g1_gen {
+            return false;
+        }
+        if self.g2_monomial.len() < 2
+            || self.g2_monomial[0] != self.opening_key.g2_gen
+            || self.g2_monomial[1] != self.opening_key.tau_g2_gen
+        {
+            return false;
+        }
+
+        // The lagrange key has at least two points, so the monomial key has `\tau G1`
+        are_consecutive_powers(monomial_points, &self.opening_key, &mut rng)
+            && are_consecutive_g2_powers(&self.g2_monomial, &monomial_points[0], &monomial_points[1], rng)
+    }
+}
+
+// Checks that `points[i+1] = \tau * points[i]` for all `i`, where `\tau` is the secret in the opening key.
+//
+// Instead of one pairing check per pair of points, we take a random linear combination
+// with coefficients `r_i` and check:
+//  e(\sum r_i * points[i], \tau G2) = e(\sum r_i * points[i+1], G2)
+fn are_consecutive_powers(points: &[blstrs::G1Affine], opening_key: &OpeningKey, mut rng: impl RngCore) -> bool {
+    use ff::Field;
+
+    let num_pairs = points.len() - 1;
+    let randomizers: Vec<_> = (0..num_pairs).map(|_| blstrs::Scalar::random(&mut rng)).collect();
+
+    let lhs = g1_lincomb(&points[..num_pairs], &randomizers);
+    let rhs = -g1_lincomb(&points[1..], &randomizers);
+
+    let terms = [(&lhs, &opening_key.prepared_beta_g2), (&rhs, &opening_key.prepared_g2)];
+    let pairing = blstrs::Bls12::multi_miller_loop(&terms).final_exponentiation();
+
+    pairing.is_identity().into()
+}
+
+// Checks that `points[i+1] = \tau * points[i]` for all `i`, where `tau_g1` is `\tau * g1_gen`.
+//
+// This is the same random linear combination as in `are_consecutive_powers`, with the roles of G1 and G2 swapped:
+//  e(\tau G1, \sum r_i * points[i]) = e(G1, \sum r_i * points[i+1])
+fn are_consecutive_g2_powers(
+    points: &[blstrs::G2Affine],
+    g1_gen: &blstrs::G1Affine,
+    tau_g1: &blstrs::G1Affine,
+    mut rng: impl RngCore,
+) -> bool {
+    use ff::Field;
+
+    let num_pairs = points.len() - 1;
+    let randomizers: Vec<_> = (0..num_pairs).map(|_| blstrs::Scalar::random(&mut rng)).collect();
+    let points: Vec<_> = points.iter().map(blstrs::G2Projective::from).collect();
+
+    let lhs = blstrs::G2Affine::from(blstrs::G2Projective::multi_exp(&points[..num_pairs], &randomizers));
+    let rhs = blstrs::G2Affine::from(blstrs::G2Projective::multi_exp(&points[1..], &randomizers));
+    let (lhs, rhs) = (blstrs::G2Prepared::from(lhs), blstrs::G2Prepared::from(rhs));
+    let neg_g1_gen = -g1_gen;
+
+    let terms = [(tau_g1, &lhs), (&neg_g1_gen, &rhs)];
+    let pairing = blstrs::Bls12::multi_miller_loop(&terms).final_exponentiation();
+
+    pairing.is_identity().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consistency_check() {
+        let domain = Domain::new(16);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+        assert!(public_parameters.verify_consistency(rand::thread_rng()));
+
+        // Commit key and opening key from different secrets
+        let other_parameters = PublicParameters::from_secret_insecure(987654321, &domain);
+        let mismatched = PublicParameters::new(
+            other_parameters.commit_key_monomial,
+            other_parameters.commit_key,
+            public_parameters.opening_key.clone(),
+        );
+        assert!(!mismatched.verify_consistency(rand::thread_rng()));
+
+        // A single corrupted lagrange point
+        let mut points = public_parameters.commit_key.points().to_vec();
+        points[5] = points[6];
+        let corrupted = PublicParameters::new(
+            public_parameters.commit_key_monomial.clone(),
+            CommitKeyLagrange::new(points),
+            public_parameters.opening_key.clone(),
+        );
+        assert!(!corrupted.verify_consistency(rand::thread_rng()));
+
+        // A single corrupted monomial point
+        let mut points = public_parameters.commit_key_monomial.points().to_vec();
+        points[15] = points[14];
+        let corrupted = PublicParameters::new(
+            CommitKey::new(points),
+            public_parameters.commit_key,
+            public_parameters.opening_key,
+        );
+        assert!(!corrupted.verify_consistency(rand::thread_rng()));
+
+        // A single corrupted G2 power
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+        let mut g2_monomial = public_parameters.g2_monomial.clone();
+        g2_monomial[4] = g2_monomial[5];
+        let corrupted = public_parameters.with_g2_monomial(g2_monomial);
+        assert!(!corrupted.verify_consistency(rand::thread_rng()));
+    }
+
+    #[test]
+    fn derived_keys_agree_with_monomial_commitments() {
+        use ff::Field;
+
+        let srs_domain = Domain::new(32);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &srs_domain);
+
+        for size in [2, 4, 8, 16, 32] {
+            let domain = Domain::new(size);
+            let commit_key = public_parameters.commit_key_for_domain(&domain);
+            #[cfg(feature = "std")]
+            assert!(Arc::ptr_eq(&commit_key, &public_parameters.commit_key_for_domain(&domain)));
+
+            let coeffs: Vec<_> = (0..size).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect();
+            let evaluations: Vec<_> = domain
+                .roots()
+                .iter()
+                .map(|root| {
+                    coeffs
+                        .iter()
+                        .enumerate()
+                        .map(|(index, coeff)| root.pow_vartime([index as u64]) * coeff)
+                        .sum()
+                })
+                .collect();
+
+            let expected = g1_lincomb(&public_parameters.commit_key_monomial.points()[..size], &coeffs);
+            let got = commit_key.commit(&crate::polynomial::Polynomial::new(evaluations));
+            assert_eq!(expected, got);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn concurrent_requests_share_derived_keys() {
+        let srs_domain = Domain::new(32);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &srs_domain);
+        let domains: Vec<_> = [4, 8, 4, 8].into_iter().map(Domain::new).collect();
+
+        let keys: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = domains
+                .iter()
+                .map(|domain| scope.spawn(|| public_parameters.commit_key_for_domain(domain)))
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+        assert!(Arc::ptr_eq(&keys[0], &keys[2]));
+        assert!(Arc::ptr_eq(&keys[1], &keys[3]));
+        assert_eq!(keys[0].points().len(), 4);
+        assert_eq!(keys[1].points().len(), 8);
     }
 }
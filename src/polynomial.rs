@@ -1,3 +1,5 @@
+use alloc::{collections::BTreeMap, sync::Arc, vec, vec::Vec};
+
 use crate::{domain::Domain, utils};
 
 use group::ff::Field;
@@ -15,7 +17,7 @@ impl PartialEq for Polynomial {
     }
 }
 
-impl std::ops::Index<usize> for &Polynomial {
+impl core::ops::Index<usize> for &Polynomial {
     type Output = blstrs::Scalar;
 
     fn index(&self, i: usize) -> &Self::Output {
@@ -58,20 +60,115 @@ impl Polynomial {
     // Using the barycentric formula, one can evaluate a polynomial
     // in evaluation form, on a point `z` that is not inside of its domain
     fn evaluate_outside_of_domain(&self, z: blstrs::Scalar, domain: &Domain) -> blstrs::Scalar {
-        let domain_size = domain.size();
+        let inverse_denominators = utils::compute_inverse_denominators(z, domain);
+        utils::evaluate_outside_domain_with_inverses(self, z, &inverse_denominators, domain)
+    }
 
-        let mut denominator: Vec<_> = domain.roots().iter().map(|root_i| z - root_i).collect();
-        utils::serial_batch_inversion(&mut denominator);
+    /// Returns the domain that this polynomial is defined over
+    ///
+    /// The domain comes from the global registry, so this can be passed to
+    /// `evaluate` and the `utils` helpers without recomputing the roots.
+    pub fn domain(&self) -> Arc<Domain> {
+        Domain::get(self.num_evaluations())
+    }
 
-        let mut result = blstrs::Scalar::zero();
-        // TODO Use zip here on evals, domain and denominator
-        for i in 0..domain_size {
-            result += (self.evaluations[i] * domain[i]) * denominator[i];
-        }
-        result * (z.pow_vartime(&[domain_size as u64]) - blstrs::Scalar::one()) * domain.domain_size_inv
+    /// Returns the coefficients of the polynomial in monomial form
+    pub fn to_coefficients(&self) -> Vec<blstrs::Scalar> {
+        self.domain().ifft_scalars(self.evaluations.clone())
     }
 
     fn num_evaluations(&self) -> usize {
         self.evaluations.len()
     }
 }
+
+/// Polynomial representation in evaluation form, where only the non-zero evaluations are stored
+///
+/// This is useful for blobs that are only partially filled with data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparsePolynomial {
+    // The number of evaluations, including the zero evaluations
+    size: usize,
+    // The non-zero evaluations, indexed by their position in the domain
+    entries: BTreeMap<usize, blstrs::Scalar>,
+}
+
+impl SparsePolynomial {
+    /// Creates a polynomial whose `size` evaluations are all zero
+    ///
+    /// Panics, if the size is not a power of two
+    pub fn new(size: usize) -> SparsePolynomial {
+        assert!(
+            size.is_power_of_two(),
+            "the domain size must be a power of two, size is : {}",
+            size
+        );
+        SparsePolynomial { size, entries: BTreeMap::new() }
+    }
+
+    pub fn from_dense(polynomial: &Polynomial) -> SparsePolynomial {
+        let mut sparse = SparsePolynomial::new(polynomial.evaluations.len());
+        for (index, evaluation) in polynomial.evaluations.iter().enumerate() {
+            sparse.insert(index, *evaluation);
+        }
+        sparse
+    }
+
+    pub fn to_dense(&self) -> Polynomial {
+        let mut evaluations = vec![blstrs::Scalar::zero(); self.size];
+        for (index, evaluation) in &self.entries {
+            evaluations[*index] = *evaluation;
+        }
+        Polynomial::new(evaluations)
+    }
+
+    /// Sets the evaluation at `index`, zero values are not stored
+    pub fn insert(&mut self, index: usize, evaluation: blstrs::Scalar) {
+        assert!(index < self.size, "index {} is out of range for size {}", index, self.size);
+        if evaluation.is_zero_vartime() {
+            self.entries.remove(&index);
+        } else {
+            self.entries.insert(index, evaluation);
+        }
+    }
+
+    pub fn get(&self, index: usize) -> blstrs::Scalar {
+        self.entries.get(&index).copied().unwrap_or_else(blstrs::Scalar::zero)
+    }
+
+    /// Returns the non-zero evaluations and their indices, in increasing order of index
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &blstrs::Scalar)> {
+        self.entries.iter().map(|(index, evaluation)| (*index, evaluation))
+    }
+
+    pub fn num_non_zero(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Evaluates the polynomial at `z`, only touching the non-zero evaluations
+    pub fn evaluate(&self, z: blstrs::Scalar, domain: &Domain) -> blstrs::Scalar {
+        assert_eq!(
+            self.size,
+            domain.size(),
+            "the size of the domain being used != the domain size of the polynomial"
+        );
+
+        if let Some(index_in_domain) = domain.find(&z) {
+            return self.get(index_in_domain);
+        }
+
+        // The barycentric formula, where the terms for zero evaluations vanish
+        let mut denominator: Vec<_> = self.entries.keys().map(|index| z - domain[*index]).collect();
+        utils::serial_batch_inversion(&mut denominator);
+
+        let mut result = blstrs::Scalar::zero();
+        for ((index, evaluation), denominator_i) in self.entries.iter().zip(denominator) {
+            result += (evaluation * domain[*index]) * denominator_i;
+        }
+        result * (z.pow_vartime([self.size as u64]) - blstrs::Scalar::one()) * domain.domain_size_inv
+    }
+}
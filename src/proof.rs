@@ -1,4 +1,6 @@
-use crate::{commit_key::*, opening_key::*, domain::Domain, polynomial::Polynomial, utils};
+use crate::opening_key::*;
+#[cfg(feature = "prover")]
+use crate::{commit_key::*, domain::Domain, polynomial::{Polynomial, SparsePolynomial}, utils};
 
 pub struct Proof {
     // Commitment to the polynomial that we have created a KZG proof for.
@@ -11,6 +13,7 @@ pub struct Proof {
 }
 
 impl Proof {
+    #[cfg(feature = "prover")]
     pub fn create(
         commit_key: &CommitKeyLagrange,
         poly: &Polynomial,
@@ -18,12 +21,80 @@ impl Proof {
         input_point: blstrs::Scalar,
         domain: &Domain,
     ) -> Proof {
-        let output_point = poly.evaluate(input_point, domain);
+        let (output_point, quotient) = match domain.find(&input_point) {
+            Some(index_in_domain) => {
+                let output_point = poly.evaluations[index_in_domain];
+                (output_point, utils::compute(poly, input_point, output_point, domain))
+            }
+            None => {
+                // The inverses of `w^i - z` are needed for both the evaluation and the quotient
+                let inverse_denominators = utils::compute_inverse_denominators(input_point, domain);
+                let output_point =
+                    utils::evaluate_outside_domain_with_inverses(poly, input_point, &inverse_denominators, domain);
+                let quotient = utils::compute_quotient_outside_domain_with_inverses(poly, output_point, inverse_denominators);
+                (output_point, quotient)
+            }
+        };
+        let quotient_commitment = commit_key.commit(&quotient);
+        Proof { polynomial_commitment: poly_comm, quotient_commitment, output_point }
+    }
+
+    /// Creates a proof, for callers that already know `output_point = p(input_point)`
+    ///
+    /// This skips the evaluation of the polynomial. `output_point` is only checked in debug builds,
+    /// an incorrect value produces a proof that does not verify.
+    #[cfg(feature = "prover")]
+    pub fn create_with_output_point(
+        commit_key: &CommitKeyLagrange,
+        poly: &Polynomial,
+        poly_comm: blstrs::G1Affine,
+        input_point: blstrs::Scalar,
+        output_point: blstrs::Scalar,
+        domain: &Domain,
+    ) -> Proof {
+        debug_assert_eq!(
+            output_point,
+            poly.evaluate(input_point, domain),
+            "output point is not the evaluation of the polynomial at the input point"
+        );
+
         let quotient = utils::compute(poly, input_point, output_point, domain);
         let quotient_commitment = commit_key.commit(&quotient);
         Proof { polynomial_commitment: poly_comm, quotient_commitment, output_point }
     }
 
+    /// Creates a proof for a polynomial whose evaluations are mostly zero
+    ///
+    /// If the input point is a domain element where the polynomial is zero, the quotient
+    /// is sparse too and only its non-zero entries are committed to. Otherwise the quotient
+    /// is dense, but the evaluation and the numerator only touch the non-zero entries.
+    #[cfg(feature = "prover")]
+    pub fn create_sparse(
+        commit_key: &CommitKeyLagrange,
+        poly: &SparsePolynomial,
+        poly_comm: blstrs::G1Affine,
+        input_point: blstrs::Scalar,
+        domain: &Domain,
+    ) -> Proof {
+        use ff::Field;
+
+        assert_eq!(poly.size(), domain.size());
+
+        match domain.find(&input_point) {
+            Some(index_in_domain) if poly.get(index_in_domain).is_zero_vartime() => {
+                let quotient = utils::compute_sparse_quotient_in_domain(poly, index_in_domain, domain);
+                let quotient_commitment = commit_key.commit_sparse(&quotient);
+                Proof { polynomial_commitment: poly_comm, quotient_commitment, output_point: blstrs::Scalar::zero() }
+            }
+            Some(_) => Proof::create(commit_key, &poly.to_dense(), poly_comm, input_point, domain),
+            None => {
+                let (output_point, quotient) = utils::compute_sparse_quotient_outside_domain(poly, input_point, domain);
+                let quotient_commitment = commit_key.commit(&quotient);
+                Proof { polynomial_commitment: poly_comm, quotient_commitment, output_point }
+            }
+        }
+    }
+
     pub fn verify(&self, input_point: blstrs::Scalar, opening_key: &OpeningKey) -> bool {
         opening_key.verify(
             input_point,
@@ -34,7 +105,7 @@ impl Proof {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "prover"))]
 mod tests {
 
     use ff::Field;
@@ -62,4 +133,123 @@ mod tests {
         assert!(proof.verify(input_point, &public_parameters.opening_key));
         assert!(!proof.verify(input_point + input_point, &public_parameters.opening_key));
     }
+
+    #[test]
+    fn proof_at_a_point_in_the_domain() {
+        let size = 2usize.pow(4);
+
+        let domain = Domain::new(size);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+
+        let poly = Polynomial::new(random_vector(size));
+        let poly_comm = public_parameters.commit_key.commit(&poly);
+
+        for index in [0, 5, size - 1] {
+            let input_point = domain.roots()[index];
+            let proof = Proof::create(&public_parameters.commit_key, &poly, poly_comm, input_point, &domain);
+            assert_eq!(proof.output_point, poly.evaluations[index]);
+            assert!(proof.verify(input_point, &public_parameters.opening_key));
+        }
+    }
+
+    #[test]
+    fn proof_with_known_output_point() {
+        let size = 2usize.pow(4);
+
+        let domain = Domain::new(size);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+        let poly = Polynomial::new(random_vector(size));
+        let poly_comm = public_parameters.commit_key.commit(&poly);
+
+        for input_point in [blstrs::Scalar::from(123456u64), domain.roots()[7]] {
+            let output_point = poly.evaluate(input_point, &domain);
+            let proof = Proof::create_with_output_point(
+                &public_parameters.commit_key,
+                &poly,
+                poly_comm,
+                input_point,
+                output_point,
+                &domain,
+            );
+            let expected = Proof::create(&public_parameters.commit_key, &poly, poly_comm, input_point, &domain);
+            assert_eq!(proof.output_point, expected.output_point);
+            assert_eq!(proof.quotient_commitment, expected.quotient_commitment);
+            assert!(proof.verify(input_point, &public_parameters.opening_key));
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "output point is not the evaluation")]
+    fn proof_with_wrong_output_point_panics_in_debug() {
+        let domain = Domain::new(4);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+        let poly = Polynomial::new(random_vector(4));
+        let poly_comm = public_parameters.commit_key.commit(&poly);
+
+        let input_point = blstrs::Scalar::from(123456u64);
+        let wrong_output_point = poly.evaluate(input_point, &domain) + blstrs::Scalar::one();
+        Proof::create_with_output_point(
+            &public_parameters.commit_key,
+            &poly,
+            poly_comm,
+            input_point,
+            wrong_output_point,
+            &domain,
+        );
+    }
+
+    #[test]
+    fn sparse_proofs_match_dense_proofs() {
+        let size = 2usize.pow(5);
+
+        let domain = Domain::new(size);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+        let commit_key = &public_parameters.commit_key;
+
+        let mut poly = SparsePolynomial::new(size);
+        poly.insert(0, blstrs::Scalar::from(42u64));
+        poly.insert(3, blstrs::Scalar::from(255u64));
+        poly.insert(17, blstrs::Scalar::random(&mut rand::thread_rng()));
+        let dense = poly.to_dense();
+        let poly_comm = commit_key.commit_sparse(&poly);
+
+        // In the domain where the polynomial is zero, in the domain where it is not, and outside of the domain
+        for input_point in [domain.roots()[5], domain.roots()[3], blstrs::Scalar::from(123456u64)] {
+            let proof = Proof::create_sparse(commit_key, &poly, poly_comm, input_point, &domain);
+            let expected = Proof::create(commit_key, &dense, poly_comm, input_point, &domain);
+            assert_eq!(proof.output_point, expected.output_point);
+            assert_eq!(proof.output_point, poly.evaluate(input_point, &domain));
+            assert_eq!(proof.quotient_commitment, expected.quotient_commitment);
+            assert!(proof.verify(input_point, &public_parameters.opening_key));
+        }
+    }
+
+    #[test]
+    fn proof_with_registry_domain() {
+        let size = 2usize.pow(4);
+
+        let poly = Polynomial::new(random_vector(size));
+        let domain = poly.domain();
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+
+        let poly_comm = public_parameters.commit_key.commit(&poly);
+        let input_point = blstrs::Scalar::from(123456u64);
+        let output_point = poly.evaluate(input_point, &domain);
+        let quotient = utils::compute(&poly, input_point, output_point, &domain);
+
+        let proof = Proof::create(&public_parameters.commit_key, &poly, poly_comm, input_point, &domain);
+        assert_eq!(proof.output_point, output_point);
+        assert_eq!(proof.quotient_commitment, public_parameters.commit_key.commit(&quotient));
+        assert!(proof.verify(input_point, &public_parameters.opening_key));
+
+        // The monomial form agrees with the evaluation form
+        let coefficients = poly.to_coefficients();
+        let expected: blstrs::Scalar = coefficients
+            .iter()
+            .enumerate()
+            .map(|(index, coeff)| input_point.pow_vartime([index as u64]) * coeff)
+            .sum();
+        assert_eq!(output_point, expected);
+    }
 }
@@ -0,0 +1,236 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use rand_core::RngCore;
+
+use crate::{
+    commit_key::Commitment,
+    danksharding::{DankshardingContext, DankshardingError, Sample},
+};
+
+/// A source of samples, such as a peer on the network
+pub trait SampleProvider {
+    /// Returns the sample at `(row, column)` of the extended data, or `None` if it is not served
+    fn get_sample(&self, row: usize, column: usize) -> Option<Sample>;
+}
+
+/// The outcome of sampling the extended data once
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingReport {
+    /// The positions that were requested
+    pub requested: Vec<(usize, usize)>,
+    /// The positions for which no sample was returned
+    pub missing: Vec<(usize, usize)>,
+    /// The positions for which the returned sample did not verify
+    pub invalid: Vec<(usize, usize)>,
+    /// The probability that unrecoverable data would have been detected by these samples,
+    /// or zero if any sample was missing or invalid
+    pub confidence: f64,
+}
+
+impl SamplingReport {
+    /// Returns true, if every requested sample was returned and verified
+    pub fn is_available(&self) -> bool {
+        self.missing.is_empty() && self.invalid.is_empty()
+    }
+}
+
+/// Samples random cells of the 2D extended data to check that it is available
+///
+/// The data can be reconstructed as long as every row or every column can be recovered from half
+/// of its cells. To make it unrecoverable, at least `k + 1` rows must each be missing more than half
+/// of their cells in the same columns, which is a fraction of about a quarter of all cells. So every
+/// sample that is returned and verifies lowers the probability that unrecoverable data goes
+/// unnoticed by a factor of about 3/4, see `SamplingClient::confidence`.
+pub struct SamplingClient<'a> {
+    context: &'a DankshardingContext,
+    num_samples: usize,
+}
+
+impl<'a> SamplingClient<'a> {
+    /// Creates a client that requests `num_samples` distinct cells each time it samples
+    ///
+    /// Panics, if there are fewer cells than samples
+    pub fn new(context: &'a DankshardingContext, num_samples: usize) -> Self {
+        let num_positions = context.num_extended_rows() * context.cell_context().num_cells();
+        assert!(num_samples <= num_positions, "cannot take {num_samples} distinct samples out of {num_positions} cells");
+        SamplingClient { context, num_samples }
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    /// Chooses `num_samples` distinct positions `(row, column)` uniformly at random
+    pub fn choose_positions(&self, rng: &mut impl RngCore) -> Vec<(usize, usize)> {
+        let num_columns = self.context.cell_context().num_cells();
+        let num_positions = self.context.num_extended_rows() * num_columns;
+
+        let mut chosen = BTreeSet::new();
+        let mut positions = Vec::with_capacity(self.num_samples);
+        while positions.len() < self.num_samples {
+            let position = random_index(rng, num_positions);
+            if chosen.insert(position) {
+                positions.push((position / num_columns, position % num_columns));
+            }
+        }
+        positions
+    }
+
+    /// Requests random samples from `provider`, and verifies them against the extended commitments
+    ///
+    /// The samples that are returned are verified in one batch. If the batch does not verify,
+    /// the samples are verified one by one to find the invalid ones.
+    ///
+    /// Note: `rng` must be a cryptographically secure rng, it is also used for the batch verification.
+    pub fn sample(
+        &self,
+        extended_commitments: &[Commitment],
+        provider: &impl SampleProvider,
+        mut rng: impl RngCore,
+    ) -> Result<SamplingReport, DankshardingError> {
+        let requested = self.choose_positions(&mut rng);
+
+        let cell_size = self.context.cell_context().cell_size();
+        let mut missing = Vec::new();
+        let mut invalid = Vec::new();
+        let mut samples = Vec::new();
+        for &(row, column) in &requested {
+            match provider.get_sample(row, column) {
+                // A cell of the wrong size would fail the whole batch with an error
+                Some(sample) if sample.row == row && sample.column == column && sample.cell.len() != cell_size => {
+                    invalid.push((row, column))
+                }
+                // A sample for another position would be verified against the wrong commitment
+                Some(sample) if sample.row == row && sample.column == column => samples.push(sample),
+                Some(_) | None => missing.push((row, column)),
+            }
+        }
+
+        if !self.context.verify_samples(extended_commitments, &samples, &mut rng)? {
+            for sample in &samples {
+                // A sample that cannot be verified on its own is as bad as one that does not verify
+                let verified =
+                    self.context.verify_samples(extended_commitments, core::slice::from_ref(sample), &mut rng);
+                if verified != Ok(true) {
+                    invalid.push((sample.row, sample.column));
+                }
+            }
+        }
+
+        let confidence = if missing.is_empty() && invalid.is_empty() { self.confidence() } else { 0.0 };
+        Ok(SamplingReport { requested, missing, invalid, confidence })
+    }
+
+    /// The probability that the samples hit a missing cell, if just enough cells are withheld to make
+    /// the data unrecoverable
+    pub fn confidence(&self) -> f64 {
+        let num_positions = self.context.num_extended_rows() * self.context.cell_context().num_cells();
+        detection_probability(num_positions, minimum_unrecoverable_cells(self.context), self.num_samples)
+    }
+}
+
+/// The smallest number of cells that make the extended data unrecoverable
+///
+/// A row or a column can be recovered from half of its cells, so `k + 1` rows that are each missing
+/// `C / 2 + 1` cells in the same columns can never be recovered.
+pub fn minimum_unrecoverable_cells(context: &DankshardingContext) -> usize {
+    (context.num_rows() + 1) * (context.cell_context().num_cells() / 2 + 1)
+}
+
+/// The probability that at least one of `num_samples` distinct samples hits one of `num_withheld`
+/// withheld cells out of `num_positions`
+///
+/// With `N` cells of which `W` are withheld, the probability that `s` distinct samples all miss the
+/// withheld cells is `\prod_{i < s} (N - W - i) / (N - i)`.
+pub fn detection_probability(num_positions: usize, num_withheld: usize, num_samples: usize) -> f64 {
+    let mut all_samples_available = 1.0;
+    for index in 0..num_samples {
+        if num_positions - index <= num_withheld {
+            return 1.0;
+        }
+        all_samples_available *= (num_positions - num_withheld - index) as f64 / (num_positions - index) as f64;
+    }
+    1.0 - all_samples_available
+}
+
+// Returns a uniformly random index below `bound`, by rejecting the values above the largest multiple of `bound`
+fn random_index(rng: &mut impl RngCore, bound: usize) -> usize {
+    let bound = bound as u64;
+    let zone = u64::MAX - (u64::MAX % bound);
+    loop {
+        let value = rng.next_u64();
+        if value < zone {
+            return (value % bound) as usize;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ff::Field;
+
+    use crate::test_utils::danksharding_setup;
+
+    use super::*;
+
+    struct Samples(HashMap<(usize, usize), Sample>);
+
+    impl SampleProvider for Samples {
+        fn get_sample(&self, row: usize, column: usize) -> Option<Sample> {
+            self.0.get(&(row, column)).cloned()
+        }
+    }
+
+    #[test]
+    fn sampling_detects_missing_and_invalid_samples() {
+        let (_, context, blobs, commitments) = danksharding_setup();
+        let extended = context.extend_blobs(&blobs, &commitments).unwrap();
+
+        let mut samples = HashMap::new();
+        for row in 0..context.num_extended_rows() {
+            for column in 0..context.cell_context().num_cells() {
+                samples.insert((row, column), extended.sample(row, column));
+            }
+        }
+        let mut provider = Samples(samples);
+
+        // Sampling every cell detects any problem
+        let client = SamplingClient::new(&context, 64);
+        let report = client.sample(&extended.commitments, &provider, rand::thread_rng()).unwrap();
+        assert!(report.is_available());
+        assert_eq!(report.confidence, 1.0);
+
+        provider.0.remove(&(1, 2));
+        provider.0.get_mut(&(6, 3)).unwrap().cell[0] += blstrs::Scalar::one();
+        let report = client.sample(&extended.commitments, &provider, rand::thread_rng()).unwrap();
+        assert!(!report.is_available());
+        assert_eq!(report.missing, vec![(1, 2)]);
+        assert_eq!(report.invalid, vec![(6, 3)]);
+        assert_eq!(report.confidence, 0.0);
+
+        // A truncated cell is reported as invalid, instead of failing the whole run
+        provider.0.get_mut(&(3, 1)).unwrap().cell.pop();
+        let mut report = client.sample(&extended.commitments, &provider, rand::thread_rng()).unwrap();
+        report.invalid.sort_unstable();
+        assert_eq!(report.missing, vec![(1, 2)]);
+        assert_eq!(report.invalid, vec![(3, 1), (6, 3)]);
+    }
+
+    #[test]
+    fn confidence_grows_with_samples() {
+        let (_, context, _, _) = danksharding_setup();
+
+        // 5 rows and 5 columns out of 8 by 8 cells
+        assert_eq!(minimum_unrecoverable_cells(&context), 25);
+        let one_sample = SamplingClient::new(&context, 1).confidence();
+        assert!((one_sample - 25.0 / 64.0).abs() < 1e-12);
+
+        let confidences: Vec<_> = (1..=40).map(|num_samples| SamplingClient::new(&context, num_samples).confidence()).collect();
+        // The confidence rounds to 1.0 in floating point well before the last sample
+        assert!(confidences.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(confidences[..10].windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(confidences[39], 1.0);
+    }
+}
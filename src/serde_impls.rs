@@ -0,0 +1,457 @@
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+
+use serde::{
+    de::{self, Error as _, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+#[cfg(feature = "prover")]
+use serde::ser::SerializeStruct;
+
+#[cfg(feature = "prover")]
+use crate::{
+    commit_key::{CommitKey, CommitKeyLagrange},
+    params::PublicParameters,
+    serialization::deserialize_g1_points_all,
+    G1_POINT_SERIALIZED_SIZE,
+};
+use crate::{
+    domain::Domain,
+    hex::{decode_hex_array, encode_hex},
+    opening_key::OpeningKey,
+    polynomial::Polynomial,
+    proof::Proof,
+    serialization::deserialize_g1_point,
+    FIELD_ELEMENTS_PER_BLOB, G2_POINT_SERIALIZED_SIZE, SCALAR_SERIALIZED_SIZE,
+};
+
+// The largest domain that is deserialized, the extended domain of a mainnet blob.
+// A domain is a single number in the input, so without a limit a few bytes could allocate
+// gigabytes of roots of unity.
+const MAX_DOMAIN_SIZE: usize = 2 * FIELD_ELEMENTS_PER_BLOB;
+
+// Points are serialized compressed and scalars as 32 big endian bytes, like everywhere else in the crate.
+// Human-readable formats get 0x prefixed hex strings, binary formats get the bytes.
+//
+// Everything is validated on deserialize: points must be in their prime order subgroup, scalars must be
+// canonical, and the sizes must be valid for the type, so the constructors never panic.
+
+fn serialize_bytes<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&encode_hex(bytes))
+    } else {
+        serializer.serialize_bytes(bytes)
+    }
+}
+
+fn deserialize_bytes<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error> {
+    if deserializer.is_human_readable() {
+        let string = String::deserialize(deserializer)?;
+        decode_hex_array(&string).map_err(D::Error::custom)
+    } else {
+        deserializer.deserialize_bytes(ByteArrayVisitor::<N>)
+    }
+}
+
+// Accepts `N` bytes, either as a byte string or as a sequence, which some binary formats use for bytes
+struct ByteArrayVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for ByteArrayVisitor<N> {
+    type Value = [u8; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{N} bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+        bytes.try_into().map_err(|_| E::invalid_length(bytes.len(), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = [0u8; N];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(index, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(A::Error::invalid_length(N + 1, &self));
+        }
+        Ok(bytes)
+    }
+}
+
+// A field element as 32 big endian bytes
+struct SerdeScalar(blstrs::Scalar);
+
+impl Serialize for SerdeScalar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(&self.0.to_bytes_be(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdeScalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = deserialize_bytes::<D, SCALAR_SERIALIZED_SIZE>(deserializer)?;
+        Option::from(blstrs::Scalar::from_bytes_be(&bytes))
+            .map(SerdeScalar)
+            .ok_or_else(|| D::Error::custom("field element is not smaller than the modulus"))
+    }
+}
+
+// A compressed G1 point, which is only decoded after the whole list has been read, see `deserialize_g1_points`
+#[cfg(feature = "prover")]
+struct CompressedG1([u8; G1_POINT_SERIALIZED_SIZE]);
+
+#[cfg(feature = "prover")]
+impl<'de> Deserialize<'de> for CompressedG1 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_bytes(deserializer).map(CompressedG1)
+    }
+}
+
+struct SerdeG1(blstrs::G1Affine);
+
+impl Serialize for SerdeG1 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(&self.0.to_compressed(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdeG1 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = deserialize_bytes(deserializer)?;
+        deserialize_g1_point(&bytes).map(SerdeG1).map_err(D::Error::custom)
+    }
+}
+
+struct SerdeG2(blstrs::G2Affine);
+
+impl Serialize for SerdeG2 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(&self.0.to_compressed(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdeG2 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = deserialize_bytes::<D, G2_POINT_SERIALIZED_SIZE>(deserializer)?;
+        Option::from(blstrs::G2Affine::from_compressed(&bytes))
+            .map(SerdeG2)
+            .ok_or_else(|| D::Error::custom("bytes are not a valid compressed G2 point in the G2 subgroup"))
+    }
+}
+
+fn serialize_scalars<S: Serializer>(scalars: &[blstrs::Scalar], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(scalars.iter().map(|scalar| SerdeScalar(*scalar)))
+}
+
+fn deserialize_scalars<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<blstrs::Scalar>, D::Error> {
+    let scalars = Vec::<SerdeScalar>::deserialize(deserializer)?;
+    Ok(scalars.into_iter().map(|scalar| scalar.0).collect())
+}
+
+// A list of G1 points, such as a commit key
+//
+// The points are decoded in one batch, which is parallel with the `parallel` feature.
+#[cfg(feature = "prover")]
+struct SerdeG1Points<'a>(&'a [blstrs::G1Affine]);
+
+#[cfg(feature = "prover")]
+impl Serialize for SerdeG1Points<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|point| SerdeG1(*point)))
+    }
+}
+
+#[cfg(feature = "prover")]
+fn deserialize_g1_points<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<blstrs::G1Affine>, D::Error> {
+    let points: Vec<_> = Vec::<CompressedG1>::deserialize(deserializer)?.into_iter().map(|point| point.0).collect();
+    deserialize_g1_points_all(&points).map_err(D::Error::custom)
+}
+
+#[cfg(feature = "prover")]
+struct SerdeG2Points<'a>(&'a [blstrs::G2Affine]);
+
+#[cfg(feature = "prover")]
+impl Serialize for SerdeG2Points<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|point| SerdeG2(*point)))
+    }
+}
+
+#[cfg(feature = "prover")]
+fn deserialize_g2_points<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<blstrs::G2Affine>, D::Error> {
+    let points = Vec::<SerdeG2>::deserialize(deserializer)?;
+    Ok(points.into_iter().map(|point| point.0).collect())
+}
+
+/// A polynomial is the list of its evaluations
+impl Serialize for Polynomial {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_scalars(&self.evaluations, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Polynomial {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let evaluations = deserialize_scalars(deserializer)?;
+        if !evaluations.len().is_power_of_two() {
+            return Err(D::Error::custom(format!(
+                "a polynomial needs a power of two number of evaluations, but received {}",
+                evaluations.len()
+            )));
+        }
+        Ok(Polynomial::new(evaluations))
+    }
+}
+
+/// A domain is only its size, the roots of unity are recomputed on deserialize
+///
+/// Sizes above 8192, twice the number of field elements in a blob, are rejected on deserialize.
+impl Serialize for Domain {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.size() as u64)
+    }
+}
+
+impl<'de> Deserialize<'de> for Domain {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let size = u64::deserialize(deserializer)?;
+        match usize::try_from(size) {
+            Ok(size) if size.is_power_of_two() && size <= MAX_DOMAIN_SIZE => Ok(Domain::new(size)),
+            _ => Err(D::Error::custom(format!(
+                "domain size must be a power of two of at most {MAX_DOMAIN_SIZE}, but received {size}"
+            ))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Proof")]
+struct ProofRepr {
+    polynomial_commitment: SerdeG1,
+    quotient_commitment: SerdeG1,
+    output_point: SerdeScalar,
+}
+
+impl Serialize for Proof {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ProofRepr {
+            polynomial_commitment: SerdeG1(self.polynomial_commitment),
+            quotient_commitment: SerdeG1(self.quotient_commitment),
+            output_point: SerdeScalar(self.output_point),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Proof {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let proof = ProofRepr::deserialize(deserializer)?;
+        Ok(Proof {
+            polynomial_commitment: proof.polynomial_commitment.0,
+            quotient_commitment: proof.quotient_commitment.0,
+            output_point: proof.output_point.0,
+        })
+    }
+}
+
+/// The opening key is serialized without the prepared G2 points, which are recomputed on deserialize
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "OpeningKey")]
+struct OpeningKeyRepr {
+    g1_gen: SerdeG1,
+    g2_gen: SerdeG2,
+    tau_g2_gen: SerdeG2,
+}
+
+impl Serialize for OpeningKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        OpeningKeyRepr { g1_gen: SerdeG1(self.g1_gen), g2_gen: SerdeG2(self.g2_gen), tau_g2_gen: SerdeG2(self.tau_g2_gen) }
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for OpeningKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = OpeningKeyRepr::deserialize(deserializer)?;
+        Ok(OpeningKey::new(key.g1_gen.0, key.g2_gen.0, key.tau_g2_gen.0))
+    }
+}
+
+/// A commit key is the list of its points
+#[cfg(feature = "prover")]
+impl Serialize for CommitKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerdeG1Points(self.points()).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "prover")]
+impl<'de> Deserialize<'de> for CommitKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let points = deserialize_g1_points(deserializer)?;
+        if points.is_empty() {
+            return Err(D::Error::custom("a commit key needs at least one point"));
+        }
+        Ok(CommitKey::new(points))
+    }
+}
+
+/// A lagrange commit key is the list of its points, the precomputed table is not serialized
+#[cfg(feature = "prover")]
+impl Serialize for CommitKeyLagrange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerdeG1Points(self.points()).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "prover")]
+impl<'de> Deserialize<'de> for CommitKeyLagrange {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let points = deserialize_g1_points(deserializer)?;
+        if points.len() < 2 {
+            return Err(D::Error::custom("a lagrange commit key needs at least two points"));
+        }
+        Ok(CommitKeyLagrange::new(points))
+    }
+}
+
+#[cfg(feature = "prover")]
+impl Serialize for PublicParameters {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PublicParameters", 4)?;
+        state.serialize_field("commit_key_monomial", &self.commit_key_monomial)?;
+        state.serialize_field("commit_key", &self.commit_key)?;
+        state.serialize_field("opening_key", &self.opening_key)?;
+        state.serialize_field("g2_monomial", &SerdeG2Points(&self.g2_monomial))?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "prover")]
+#[derive(Deserialize)]
+#[serde(rename = "PublicParameters")]
+struct PublicParametersRepr {
+    commit_key_monomial: CommitKey,
+    commit_key: CommitKeyLagrange,
+    opening_key: OpeningKey,
+    #[serde(deserialize_with = "deserialize_g2_points")]
+    g2_monomial: Vec<blstrs::G2Affine>,
+}
+
+/// Only the encodings and sizes are checked, use `PublicParameters::verify_consistency`
+/// to check that the keys come from the same secret.
+#[cfg(feature = "prover")]
+impl<'de> Deserialize<'de> for PublicParameters {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let params = PublicParametersRepr::deserialize(deserializer)?;
+        let opening_key = &params.opening_key;
+        if params.g2_monomial.len() < 2
+            || params.g2_monomial[0] != opening_key.g2_gen
+            || params.g2_monomial[1] != opening_key.tau_g2_gen
+        {
+            return Err(D::Error::custom("G2 points do not match the opening key"));
+        }
+        Ok(PublicParameters::new(params.commit_key_monomial, params.commit_key, params.opening_key)
+            .with_g2_monomial(params.g2_monomial))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use group::Group;
+
+    use super::*;
+
+    fn random_polynomial(size: usize) -> Polynomial {
+        Polynomial::new((0..size).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect())
+    }
+
+    #[test]
+    fn json_uses_hex() {
+        let polynomial = random_polynomial(4);
+        let json = serde_json::to_string(&polynomial).unwrap();
+        let expected: Vec<_> = polynomial.evaluations.iter().map(|scalar| encode_hex(&scalar.to_bytes_be())).collect();
+        assert_eq!(json, serde_json::to_string(&expected).unwrap());
+        assert_eq!(serde_json::from_str::<Polynomial>(&json).unwrap(), polynomial);
+
+        let proof = Proof {
+            polynomial_commitment: blstrs::G1Projective::random(&mut rand::thread_rng()).into(),
+            quotient_commitment: blstrs::G1Projective::random(&mut rand::thread_rng()).into(),
+            output_point: blstrs::Scalar::random(&mut rand::thread_rng()),
+        };
+        let json = serde_json::to_value(&proof).unwrap();
+        assert_eq!(json["quotient_commitment"], encode_hex(&proof.quotient_commitment.to_compressed()));
+        let decoded: Proof = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.polynomial_commitment, proof.polynomial_commitment);
+        assert_eq!(decoded.quotient_commitment, proof.quotient_commitment);
+        assert_eq!(decoded.output_point, proof.output_point);
+
+        let domain: Domain = serde_json::from_str(&serde_json::to_string(&Domain::new(16)).unwrap()).unwrap();
+        assert_eq!(domain.roots, Domain::new(16).roots);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let modulus = encode_hex(&[0xff; SCALAR_SERIALIZED_SIZE]);
+        let error = serde_json::from_str::<Polynomial>(&format!(r#"["{modulus}"]"#)).unwrap_err();
+        assert!(error.to_string().contains("not smaller than the modulus"), "{error}");
+
+        let zero = encode_hex(&[0; SCALAR_SERIALIZED_SIZE]);
+        let error = serde_json::from_str::<Polynomial>(&format!(r#"["{zero}","{zero}","{zero}"]"#)).unwrap_err();
+        assert!(error.to_string().contains("power of two"), "{error}");
+
+        for size in ["0", "12", "16384", "4294967296", "8589934592"] {
+            assert!(serde_json::from_str::<Domain>(size).is_err(), "{size}");
+        }
+        assert_eq!(serde_json::from_str::<Domain>("8192").unwrap().size(), MAX_DOMAIN_SIZE);
+
+        // The compression flag is cleared, so this is not a valid compressed point
+        let mut g1_gen = blstrs::G1Affine::from(blstrs::G1Projective::generator()).to_compressed();
+        g1_gen[0] &= 0x7f;
+        let g2_gen = encode_hex(&blstrs::G2Affine::from(blstrs::G2Projective::generator()).to_compressed());
+        let json = format!(r#"{{"g1_gen":"{}","g2_gen":"{g2_gen}","tau_g2_gen":"{g2_gen}"}}"#, encode_hex(&g1_gen));
+        let error = serde_json::from_str::<OpeningKey>(&json).unwrap_err();
+        assert!(error.to_string().contains("not a valid compressed G1 point"), "{error}");
+    }
+
+    #[test]
+    fn binary_formats_use_bytes() {
+        let opening_key = crate::verifier::mainnet_opening_key();
+        let bytes = bincode::serialize(&opening_key).unwrap();
+        // Each point is prefixed with its length
+        assert_eq!(bytes.len(), crate::OPENING_KEY_SERIALIZED_SIZE + 3 * 8);
+        let decoded: OpeningKey = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), opening_key.to_bytes());
+
+        let polynomial = random_polynomial(8);
+        let decoded: Polynomial = bincode::deserialize(&bincode::serialize(&polynomial).unwrap()).unwrap();
+        assert_eq!(decoded, polynomial);
+    }
+
+    #[cfg(feature = "prover")]
+    #[test]
+    fn public_parameters_round_trip() {
+        let domain = Domain::new(8);
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &domain);
+
+        for decoded in [
+            serde_json::from_str::<PublicParameters>(&serde_json::to_string(&public_parameters).unwrap()).unwrap(),
+            bincode::deserialize::<PublicParameters>(&bincode::serialize(&public_parameters).unwrap()).unwrap(),
+        ] {
+            assert_eq!(decoded.commit_key.points(), public_parameters.commit_key.points());
+            assert_eq!(decoded.commit_key_monomial.points(), public_parameters.commit_key_monomial.points());
+            assert_eq!(decoded.opening_key.to_bytes(), public_parameters.opening_key.to_bytes());
+            assert_eq!(decoded.g2_monomial, public_parameters.g2_monomial);
+            assert!(decoded.verify_consistency(rand::thread_rng()));
+        }
+
+        let mut json = serde_json::to_value(&public_parameters).unwrap();
+        json["g2_monomial"].as_array_mut().unwrap().swap(0, 1);
+        let Err(error) = serde_json::from_value::<PublicParameters>(json) else {
+            panic!("G2 points that do not match the opening key were accepted");
+        };
+        assert!(error.to_string().contains("do not match the opening key"), "{error}");
+    }
+}
@@ -0,0 +1,205 @@
+use alloc::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{polynomial::Polynomial, utils::reverse_bit_order, G1_POINT_SERIALIZED_SIZE, SCALAR_SERIALIZED_SIZE};
+
+/// Errors that can occur when deserializing a compressed G1 point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointDecodingError {
+    /// The bytes are not a valid compressed encoding of a point on the curve
+    InvalidEncoding,
+    /// The point is on the curve, but not in the prime order subgroup
+    NotInSubgroup,
+}
+
+impl core::fmt::Display for PointDecodingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PointDecodingError::InvalidEncoding => write!(f, "bytes are not a valid compressed G1 point"),
+            PointDecodingError::NotInSubgroup => write!(f, "point is not in the G1 subgroup"),
+        }
+    }
+}
+
+impl core::error::Error for PointDecodingError {}
+
+/// The first point in a batch that could not be deserialized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchDecodingError {
+    pub index: usize,
+    pub error: PointDecodingError,
+}
+
+impl core::fmt::Display for BatchDecodingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "point at index {}: {}", self.index, self.error)
+    }
+}
+
+impl core::error::Error for BatchDecodingError {}
+
+/// Errors that can occur when deserializing a blob
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobDecodingError {
+    /// The blob is not a power of two number of 32 byte field elements
+    InvalidLength { length: usize },
+    /// The field element is not smaller than the modulus
+    NonCanonicalFieldElement { index: usize },
+}
+
+impl core::fmt::Display for BlobDecodingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BlobDecodingError::InvalidLength { length } => {
+                write!(f, "blob of {length} bytes is not a power of two number of field elements")
+            }
+            BlobDecodingError::NonCanonicalFieldElement { index } => {
+                write!(f, "field element {index} of the blob is not canonical")
+            }
+        }
+    }
+}
+
+impl core::error::Error for BlobDecodingError {}
+
+/// Deserializes a single compressed G1 point, checking that it is in the subgroup
+pub fn deserialize_g1_point(bytes: &[u8; G1_POINT_SERIALIZED_SIZE]) -> Result<blstrs::G1Affine, PointDecodingError> {
+    let point: Option<blstrs::G1Affine> = blstrs::G1Affine::from_compressed_unchecked(bytes).into();
+    let point = point.ok_or(PointDecodingError::InvalidEncoding)?;
+    if !bool::from(point.is_torsion_free()) {
+        return Err(PointDecodingError::NotInSubgroup);
+    }
+    Ok(point)
+}
+
+/// Deserializes many compressed G1 points, such as the commitments and proofs in a block
+///
+/// The result has one entry per input, so a caller can tell exactly which points were invalid.
+///
+/// The cost is dominated by the square root in the decompression and the subgroup check.
+/// blst computes both without field inversions (the square root is an exponentiation and the
+/// subgroup check works in projective coordinates), so there is nothing to share between points,
+/// but every point is independent. With the `parallel` feature, the points are decoded in parallel.
+pub fn deserialize_g1_points(
+    bytes: &[[u8; G1_POINT_SERIALIZED_SIZE]],
+) -> Vec<Result<blstrs::G1Affine, PointDecodingError>> {
+    #[cfg(feature = "parallel")]
+    let points = bytes.par_iter().map(deserialize_g1_point).collect();
+    #[cfg(not(feature = "parallel"))]
+    let points = bytes.iter().map(deserialize_g1_point).collect();
+    points
+}
+
+/// Deserializes many compressed G1 points, failing on the first invalid point
+///
+/// All of the points are still decoded in one batch, see `deserialize_g1_points`.
+pub fn deserialize_g1_points_all(
+    bytes: &[[u8; G1_POINT_SERIALIZED_SIZE]],
+) -> Result<Vec<blstrs::G1Affine>, BatchDecodingError> {
+    deserialize_g1_points(bytes)
+        .into_iter()
+        .enumerate()
+        .map(|(index, point)| point.map_err(|error| BatchDecodingError { index, error }))
+        .collect()
+}
+
+/// Serializes a blob in the consensus specs' format
+///
+/// The specs store the evaluations in bit reversed order, as 32 byte big endian field elements.
+pub fn serialize_blob(blob: &Polynomial) -> Vec<u8> {
+    let mut evaluations = blob.evaluations.clone();
+    reverse_bit_order(&mut evaluations);
+    evaluations.iter().flat_map(|evaluation| evaluation.to_bytes_be()).collect()
+}
+
+/// Deserializes a blob in the consensus specs' format, see `serialize_blob`
+pub fn deserialize_blob(bytes: &[u8]) -> Result<Polynomial, BlobDecodingError> {
+    let num_field_elements = bytes.len() / SCALAR_SERIALIZED_SIZE;
+    if !bytes.len().is_multiple_of(SCALAR_SERIALIZED_SIZE) || !num_field_elements.is_power_of_two() {
+        return Err(BlobDecodingError::InvalidLength { length: bytes.len() });
+    }
+
+    let mut evaluations = bytes
+        .chunks_exact(SCALAR_SERIALIZED_SIZE)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let scalar: Option<blstrs::Scalar> = blstrs::Scalar::from_bytes_be(chunk.try_into().unwrap()).into();
+            scalar.ok_or(BlobDecodingError::NonCanonicalFieldElement { index })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    reverse_bit_order(&mut evaluations);
+    Ok(Polynomial::new(evaluations))
+}
+
+#[cfg(test)]
+mod tests {
+    use group::{prime::PrimeCurveAffine, Group};
+
+    use super::*;
+
+    // A point on the curve that is not in the subgroup
+    //
+    // The cofactor of G1 is large, so almost every point on the curve is outside of the subgroup.
+    fn point_outside_of_subgroup() -> [u8; G1_POINT_SERIALIZED_SIZE] {
+        (1u8..)
+            .map(|x| {
+                // The compression flag is set, the big endian x coordinate is `x`
+                let mut bytes = [0u8; G1_POINT_SERIALIZED_SIZE];
+                bytes[0] = 0x80;
+                bytes[G1_POINT_SERIALIZED_SIZE - 1] = x;
+                bytes
+            })
+            .find(|bytes| {
+                let point: Option<blstrs::G1Affine> = blstrs::G1Affine::from_compressed_unchecked(bytes).into();
+                point.is_some()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn batch_matches_single_point_decoding() {
+        let points: Vec<blstrs::G1Affine> = (0..10)
+            .map(|_| blstrs::G1Projective::random(&mut rand::thread_rng()).into())
+            .chain(core::iter::once(blstrs::G1Affine::identity()))
+            .collect();
+        let mut bytes: Vec<_> = points.iter().map(|point| point.to_compressed()).collect();
+
+        assert_eq!(deserialize_g1_points_all(&bytes).unwrap(), points);
+
+        // Clearing the compression flag is an invalid encoding
+        bytes[3][0] &= 0x7f;
+        bytes[7] = point_outside_of_subgroup();
+
+        let decoded = deserialize_g1_points(&bytes);
+        assert_eq!(decoded.len(), points.len());
+        for (index, (result, point)) in decoded.iter().zip(&points).enumerate() {
+            match index {
+                3 => assert_eq!(*result, Err(PointDecodingError::InvalidEncoding)),
+                7 => assert_eq!(*result, Err(PointDecodingError::NotInSubgroup)),
+                _ => assert_eq!(*result, Ok(*point)),
+            }
+        }
+
+        assert_eq!(
+            deserialize_g1_points_all(&bytes).unwrap_err(),
+            BatchDecodingError { index: 3, error: PointDecodingError::InvalidEncoding }
+        );
+    }
+
+    #[test]
+    fn blob_round_trip_in_bit_reversed_order() {
+        let blob = Polynomial::new((0..8u64).map(blstrs::Scalar::from).collect());
+        let bytes = serialize_blob(&blob);
+        assert_eq!(bytes.len(), 8 * SCALAR_SERIALIZED_SIZE);
+        // The second field element in the specs' order is the evaluation at index 4
+        assert_eq!(bytes[2 * SCALAR_SERIALIZED_SIZE - 1], 4);
+        assert_eq!(deserialize_blob(&bytes).unwrap(), blob);
+
+        assert_eq!(deserialize_blob(&bytes[1..]), Err(BlobDecodingError::InvalidLength { length: 255 }));
+        let mut non_canonical = bytes;
+        non_canonical[3 * SCALAR_SERIALIZED_SIZE..4 * SCALAR_SERIALIZED_SIZE].fill(0xff);
+        assert_eq!(deserialize_blob(&non_canonical), Err(BlobDecodingError::NonCanonicalFieldElement { index: 3 }));
+    }
+}
@@ -0,0 +1,228 @@
+use alloc::{collections::BTreeSet, sync::Arc, vec::Vec};
+
+use rand_core::RngCore;
+
+use crate::{
+    commit_key::Commitment,
+    danksharding::{DankshardingContext, DankshardingError, ExtendedBlobs, Sample},
+    sampling::{detection_probability, minimum_unrecoverable_cells, SampleProvider, SamplingClient},
+};
+
+/// Which cells a simulated full node does not serve
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Withholding {
+    /// Serve every cell
+    None,
+    /// Withhold each cell independently with the given probability
+    Random { fraction: f64 },
+    /// Withhold the `rows x columns` block in the top left corner, the cheapest way to make the data
+    /// unrecoverable is `(k + 1) x (C / 2 + 1)`
+    Block { rows: usize, columns: usize },
+    /// Withhold the smallest block that makes the data unrecoverable
+    MinimalUnrecoverable,
+}
+
+/// An in-process full node that holds the extended data, but may withhold some of it
+pub struct FullNode {
+    extended: Arc<ExtendedBlobs>,
+    withheld: BTreeSet<(usize, usize)>,
+}
+
+impl FullNode {
+    /// Creates a node that serves `extended`, except for the cells chosen by `withholding`
+    pub fn new(
+        context: &DankshardingContext,
+        extended: Arc<ExtendedBlobs>,
+        withholding: Withholding,
+        rng: &mut impl RngCore,
+    ) -> FullNode {
+        let num_rows = context.num_extended_rows();
+        let num_columns = context.cell_context().num_cells();
+        let positions = (0..num_rows).flat_map(|row| (0..num_columns).map(move |column| (row, column)));
+
+        let withheld = match withholding {
+            Withholding::None => BTreeSet::new(),
+            Withholding::Random { fraction } => {
+                // Compare against a uniform value in [0, 1) with 53 bits of precision
+                let mut uniform = || (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+                positions.filter(|_| uniform() < fraction).collect()
+            }
+            Withholding::Block { rows, columns } => {
+                positions.filter(|(row, column)| *row < rows && *column < columns).collect()
+            }
+            Withholding::MinimalUnrecoverable => {
+                let (rows, columns) = (context.num_rows() + 1, num_columns / 2 + 1);
+                positions.filter(|(row, column)| *row < rows && *column < columns).collect()
+            }
+        };
+
+        FullNode { extended, withheld }
+    }
+
+    /// The number of cells this node does not serve
+    pub fn num_withheld(&self) -> usize {
+        self.withheld.len()
+    }
+}
+
+impl SampleProvider for FullNode {
+    fn get_sample(&self, row: usize, column: usize) -> Option<Sample> {
+        if self.withheld.contains(&(row, column)) {
+            return None;
+        }
+        let cell = self.extended.cells.get(row)?.get(column)?.clone();
+        Some(Sample { row, column, cell, proof: self.extended.proofs[row][column] })
+    }
+}
+
+/// A set of full nodes, a sample is available if any of the nodes serves it
+pub struct Network {
+    pub nodes: Vec<FullNode>,
+}
+
+impl SampleProvider for Network {
+    fn get_sample(&self, row: usize, column: usize) -> Option<Sample> {
+        self.nodes.iter().find_map(|node| node.get_sample(row, column))
+    }
+}
+
+/// The detection rate over many sampling clients
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationReport {
+    /// The number of clients that sampled the network
+    pub num_clients: usize,
+    /// The number of clients that found a missing or invalid sample
+    pub num_detected: usize,
+    /// The fraction of cells that none of the nodes serve
+    pub withheld_fraction: f64,
+    /// The probability that a client detects withholding of `withheld_fraction` of the cells,
+    /// when the withheld cells are chosen independently of the samples
+    pub expected_detection_rate: f64,
+}
+
+impl SimulationReport {
+    /// The fraction of clients that detected the withholding, 0 if there were no clients
+    pub fn detection_rate(&self) -> f64 {
+        if self.num_clients == 0 {
+            return 0.0;
+        }
+        self.num_detected as f64 / self.num_clients as f64
+    }
+}
+
+/// Runs sampling clients against a network of full nodes, without a real network
+///
+/// Each client chooses its own random cells and verifies the samples, so the fraction of clients
+/// that notice the withheld data can be compared between sampling strategies.
+pub struct Simulation<'a> {
+    context: &'a DankshardingContext,
+    extended_commitments: Vec<Commitment>,
+    network: Network,
+}
+
+impl<'a> Simulation<'a> {
+    pub fn new(context: &'a DankshardingContext, extended_commitments: Vec<Commitment>, network: Network) -> Self {
+        Simulation { context, extended_commitments, network }
+    }
+
+    /// Creates a network of nodes that all hold `extended`, each with its own withholding
+    pub fn with_nodes(
+        context: &'a DankshardingContext,
+        extended: ExtendedBlobs,
+        withholdings: &[Withholding],
+        rng: &mut impl RngCore,
+    ) -> Self {
+        let extended_commitments = extended.commitments.clone();
+        let extended = Arc::new(extended);
+        let nodes = withholdings
+            .iter()
+            .map(|withholding| FullNode::new(context, extended.clone(), *withholding, rng))
+            .collect();
+        Simulation::new(context, extended_commitments, Network { nodes })
+    }
+
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    /// Runs `num_clients` clients that each take `num_samples` samples
+    ///
+    /// Note: `rng` must be a cryptographically secure rng, it is also used for the batch verification.
+    pub fn run(
+        &self,
+        num_clients: usize,
+        num_samples: usize,
+        mut rng: impl RngCore,
+    ) -> Result<SimulationReport, DankshardingError> {
+        let client = SamplingClient::new(self.context, num_samples);
+
+        let mut num_detected = 0;
+        for _ in 0..num_clients {
+            let report = client.sample(&self.extended_commitments, &self.network, &mut rng)?;
+            if !report.is_available() {
+                num_detected += 1;
+            }
+        }
+
+        let num_positions = self.context.num_extended_rows() * self.context.cell_context().num_cells();
+        let num_withheld = self.num_withheld();
+        Ok(SimulationReport {
+            num_clients,
+            num_detected,
+            withheld_fraction: num_withheld as f64 / num_positions as f64,
+            expected_detection_rate: detection_probability(num_positions, num_withheld, num_samples),
+        })
+    }
+
+    /// Returns true, if enough cells are withheld that the data may be unrecoverable
+    pub fn may_be_unrecoverable(&self) -> bool {
+        self.num_withheld() >= minimum_unrecoverable_cells(self.context)
+    }
+
+    // The number of cells that none of the nodes serve
+    fn num_withheld(&self) -> usize {
+        let num_columns = self.context.cell_context().num_cells();
+        (0..self.context.num_extended_rows())
+            .flat_map(|row| (0..num_columns).map(move |column| (row, column)))
+            .filter(|(row, column)| self.network.get_sample(*row, *column).is_none())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::danksharding_setup;
+
+    use super::*;
+
+    #[test]
+    fn detection_rate_matches_expectation() {
+        let (_, context, blobs, commitments) = danksharding_setup();
+        let extended = context.extend_blobs(&blobs, &commitments).unwrap();
+        let mut rng = rand::thread_rng();
+
+        // Honest nodes are never flagged, even if one of them withholds everything it can
+        let honest = Simulation::with_nodes(
+            &context,
+            extended.clone(),
+            &[Withholding::None, Withholding::Random { fraction: 1.0 }],
+            &mut rng,
+        );
+        assert_eq!(honest.network().nodes[1].num_withheld(), 64);
+        assert!(!honest.may_be_unrecoverable());
+        let report = honest.run(10, 8, &mut rng).unwrap();
+        assert_eq!(report.num_detected, 0);
+        assert_eq!(report.expected_detection_rate, 0.0);
+        assert_eq!(honest.run(0, 8, &mut rng).unwrap().detection_rate(), 0.0);
+
+        // The minimal unrecoverable block is 5 x 5 cells out of 8 x 8
+        let adversarial = Simulation::with_nodes(&context, extended, &[Withholding::MinimalUnrecoverable], &mut rng);
+        assert!(adversarial.may_be_unrecoverable());
+        let report = adversarial.run(200, 4, &mut rng).unwrap();
+        assert_eq!(report.withheld_fraction, 25.0 / 64.0);
+        assert_eq!(report.expected_detection_rate, SamplingClient::new(&context, 4).confidence());
+
+        // Each client detects with probability ~0.87, so the standard deviation of the rate is ~0.025
+        assert!((report.detection_rate() - report.expected_detection_rate).abs() < 0.15);
+    }
+}
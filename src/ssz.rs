@@ -0,0 +1,700 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "prover")]
+use crate::context::{KzgContext, KzgContextError};
+use crate::{
+    polynomial::Polynomial,
+    serialization::{deserialize_blob, deserialize_g1_point, serialize_blob, BlobDecodingError, PointDecodingError},
+    verifier::{self, Verifier, VerifierError},
+    BYTES_PER_BLOB, G1_POINT_SERIALIZED_SIZE, SCALAR_SERIALIZED_SIZE,
+};
+
+/// A 32 byte SSZ hash tree root
+pub type Root = [u8; 32];
+
+// The number of bytes in a BLS signature
+const BLS_SIGNATURE_SIZE: usize = 96;
+// The number of bytes in a chunk of the SSZ merkle tree
+const BYTES_PER_CHUNK: usize = 32;
+
+/// The maximum length of the `blob_kzg_commitments` list in a block body
+pub const MAX_BLOB_COMMITMENTS_PER_BLOCK: usize = 4096;
+/// The index of `blob_kzg_commitments` in the fields of `BeaconBlockBody`
+pub const BLOB_KZG_COMMITMENTS_INDEX: usize = 11;
+// The depth of the tree over the fields of `BeaconBlockBody`, which has at most 16 fields
+const BLOCK_BODY_TREE_DEPTH: usize = 4;
+// log2(MAX_BLOB_COMMITMENTS_PER_BLOCK)
+const COMMITMENTS_TREE_DEPTH: usize = 12;
+/// The number of hashes in the inclusion proof of a commitment in a block body: the path in the list,
+/// the length of the list, and the path in the block body
+pub const KZG_COMMITMENT_INCLUSION_PROOF_DEPTH: usize = COMMITMENTS_TREE_DEPTH + 1 + BLOCK_BODY_TREE_DEPTH;
+
+/// Errors that can occur when SSZ decoding or when building an inclusion proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SszError {
+    /// The bytes do not have the size of the type
+    InvalidLength { expected: usize, received: usize },
+    /// There are more commitments than fit in a block body
+    TooManyCommitments { num_commitments: usize },
+    /// The commitment index is not in the list of commitments
+    InvalidCommitmentIndex { index: usize, num_commitments: usize },
+    /// The block body must have at least `BLOB_KZG_COMMITMENTS_INDEX + 1` and at most 16 fields
+    InvalidBlockBodyFieldCount { num_fields: usize },
+    /// The root of the `blob_kzg_commitments` field is not the root of the commitments
+    CommitmentsRootMismatch,
+}
+
+impl core::fmt::Display for SszError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SszError::InvalidLength { expected, received } => {
+                write!(f, "expected {expected} bytes, but received {received} bytes")
+            }
+            SszError::TooManyCommitments { num_commitments } => write!(
+                f,
+                "{num_commitments} commitments do not fit in a block body, the maximum is {MAX_BLOB_COMMITMENTS_PER_BLOCK}"
+            ),
+            SszError::InvalidCommitmentIndex { index, num_commitments } => {
+                write!(f, "commitment index {index} is out of range for {num_commitments} commitments")
+            }
+            SszError::InvalidBlockBodyFieldCount { num_fields } => {
+                write!(f, "a block body with {num_fields} fields cannot hold the blob commitments")
+            }
+            SszError::CommitmentsRootMismatch => {
+                write!(f, "the block body's blob_kzg_commitments root does not match the commitments")
+            }
+        }
+    }
+}
+
+impl core::error::Error for SszError {}
+
+/// Errors that can occur when validating a blob sidecar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SidecarError {
+    Blob(BlobDecodingError),
+    Commitment(PointDecodingError),
+    Proof(PointDecodingError),
+    Verifier(VerifierError),
+    #[cfg(feature = "prover")]
+    Context(KzgContextError),
+}
+
+impl core::fmt::Display for SidecarError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SidecarError::Blob(error) => write!(f, "invalid blob: {error}"),
+            SidecarError::Commitment(error) => write!(f, "invalid commitment: {error}"),
+            SidecarError::Proof(error) => write!(f, "invalid proof: {error}"),
+            SidecarError::Verifier(error) => write!(f, "{error}"),
+            #[cfg(feature = "prover")]
+            SidecarError::Context(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl core::error::Error for SidecarError {}
+
+#[cfg(feature = "prover")]
+impl From<KzgContextError> for SidecarError {
+    fn from(error: KzgContextError) -> Self {
+        SidecarError::Context(error)
+    }
+}
+
+/// SSZ serialization and merkleization of a fixed size type
+pub trait Ssz: Sized {
+    /// The number of bytes in the serialization
+    const SSZ_SIZE: usize;
+
+    /// Appends the serialization to `bytes`
+    fn ssz_append(&self, bytes: &mut Vec<u8>);
+
+    /// Deserializes from exactly `SSZ_SIZE` bytes
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, SszError>;
+
+    fn hash_tree_root(&self) -> Root;
+
+    fn to_ssz_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::SSZ_SIZE);
+        self.ssz_append(&mut bytes);
+        bytes
+    }
+}
+
+impl Ssz for u64 {
+    const SSZ_SIZE: usize = 8;
+
+    fn ssz_append(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, SszError> {
+        check_length(bytes, Self::SSZ_SIZE)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        let mut root = [0u8; 32];
+        root[..8].copy_from_slice(&self.to_le_bytes());
+        root
+    }
+}
+
+impl Ssz for Root {
+    const SSZ_SIZE: usize = 32;
+
+    fn ssz_append(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, SszError> {
+        check_length(bytes, Self::SSZ_SIZE)?;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        *self
+    }
+}
+
+// Implements `Ssz` for a newtype around a byte vector of length `$size`
+macro_rules! impl_ssz_for_bytes {
+    ($name:ident, $size:expr) => {
+        impl Ssz for $name {
+            const SSZ_SIZE: usize = $size;
+
+            fn ssz_append(&self, bytes: &mut Vec<u8>) {
+                bytes.extend_from_slice(&self.0[..]);
+            }
+
+            fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, SszError> {
+                check_length(bytes, Self::SSZ_SIZE)?;
+                Ok($name(bytes.to_vec().try_into().unwrap()))
+            }
+
+            fn hash_tree_root(&self) -> Root {
+                hash_tree_root_of_bytes(&self.0[..])
+            }
+        }
+    };
+}
+
+/// A compressed commitment to a blob, `KZGCommitment` in the consensus specs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KzgCommitment(pub [u8; G1_POINT_SERIALIZED_SIZE]);
+
+/// A compressed proof for a blob, `KZGProof` in the consensus specs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KzgProof(pub [u8; G1_POINT_SERIALIZED_SIZE]);
+
+/// A BLS signature, which is carried along but not checked by this crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlsSignature(pub [u8; BLS_SIGNATURE_SIZE]);
+
+/// A blob in the consensus specs' serialization, see `serialization::serialize_blob`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blob(pub Box<[u8; BYTES_PER_BLOB]>);
+
+impl_ssz_for_bytes!(KzgCommitment, G1_POINT_SERIALIZED_SIZE);
+impl_ssz_for_bytes!(KzgProof, G1_POINT_SERIALIZED_SIZE);
+impl_ssz_for_bytes!(BlsSignature, BLS_SIGNATURE_SIZE);
+impl_ssz_for_bytes!(Blob, BYTES_PER_BLOB);
+
+impl From<blstrs::G1Affine> for KzgCommitment {
+    fn from(point: blstrs::G1Affine) -> Self {
+        KzgCommitment(point.to_compressed())
+    }
+}
+
+impl KzgCommitment {
+    pub fn to_point(&self) -> Result<blstrs::G1Affine, PointDecodingError> {
+        deserialize_g1_point(&self.0)
+    }
+}
+
+impl From<blstrs::G1Affine> for KzgProof {
+    fn from(point: blstrs::G1Affine) -> Self {
+        KzgProof(point.to_compressed())
+    }
+}
+
+impl KzgProof {
+    pub fn to_point(&self) -> Result<blstrs::G1Affine, PointDecodingError> {
+        deserialize_g1_point(&self.0)
+    }
+}
+
+impl Blob {
+    /// Serializes a mainnet sized blob
+    ///
+    /// Panics, if the polynomial does not have `FIELD_ELEMENTS_PER_BLOB` evaluations
+    pub fn from_polynomial(polynomial: &Polynomial) -> Self {
+        Blob(serialize_blob(polynomial).try_into().expect("polynomial is not the size of a blob"))
+    }
+
+    pub fn to_polynomial(&self) -> Result<Polynomial, BlobDecodingError> {
+        deserialize_blob(&self.0[..])
+    }
+}
+
+/// `BeaconBlockHeader` in the consensus specs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BeaconBlockHeader {
+    pub slot: u64,
+    pub proposer_index: u64,
+    pub parent_root: Root,
+    pub state_root: Root,
+    pub body_root: Root,
+}
+
+impl Ssz for BeaconBlockHeader {
+    const SSZ_SIZE: usize = 8 + 8 + 32 + 32 + 32;
+
+    fn ssz_append(&self, bytes: &mut Vec<u8>) {
+        self.slot.ssz_append(bytes);
+        self.proposer_index.ssz_append(bytes);
+        self.parent_root.ssz_append(bytes);
+        self.state_root.ssz_append(bytes);
+        self.body_root.ssz_append(bytes);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, SszError> {
+        check_length(bytes, Self::SSZ_SIZE)?;
+        let mut fields = Fields(bytes);
+        Ok(BeaconBlockHeader {
+            slot: fields.next()?,
+            proposer_index: fields.next()?,
+            parent_root: fields.next()?,
+            state_root: fields.next()?,
+            body_root: fields.next()?,
+        })
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        merkleize(&[
+            self.slot.hash_tree_root(),
+            self.proposer_index.hash_tree_root(),
+            self.parent_root,
+            self.state_root,
+            self.body_root,
+        ])
+    }
+}
+
+/// `SignedBeaconBlockHeader` in the consensus specs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedBeaconBlockHeader {
+    pub message: BeaconBlockHeader,
+    pub signature: BlsSignature,
+}
+
+impl Ssz for SignedBeaconBlockHeader {
+    const SSZ_SIZE: usize = BeaconBlockHeader::SSZ_SIZE + BlsSignature::SSZ_SIZE;
+
+    fn ssz_append(&self, bytes: &mut Vec<u8>) {
+        self.message.ssz_append(bytes);
+        self.signature.ssz_append(bytes);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, SszError> {
+        check_length(bytes, Self::SSZ_SIZE)?;
+        let mut fields = Fields(bytes);
+        Ok(SignedBeaconBlockHeader { message: fields.next()?, signature: fields.next()? })
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        merkleize(&[self.message.hash_tree_root(), self.signature.hash_tree_root()])
+    }
+}
+
+/// `BlobSidecar` in the consensus specs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobSidecar {
+    pub index: u64,
+    pub blob: Blob,
+    pub kzg_commitment: KzgCommitment,
+    pub kzg_proof: KzgProof,
+    pub signed_block_header: SignedBeaconBlockHeader,
+    pub kzg_commitment_inclusion_proof: [Root; KZG_COMMITMENT_INCLUSION_PROOF_DEPTH],
+}
+
+impl Ssz for BlobSidecar {
+    const SSZ_SIZE: usize = 8
+        + Blob::SSZ_SIZE
+        + KzgCommitment::SSZ_SIZE
+        + KzgProof::SSZ_SIZE
+        + SignedBeaconBlockHeader::SSZ_SIZE
+        + KZG_COMMITMENT_INCLUSION_PROOF_DEPTH * 32;
+
+    fn ssz_append(&self, bytes: &mut Vec<u8>) {
+        self.index.ssz_append(bytes);
+        self.blob.ssz_append(bytes);
+        self.kzg_commitment.ssz_append(bytes);
+        self.kzg_proof.ssz_append(bytes);
+        self.signed_block_header.ssz_append(bytes);
+        for root in &self.kzg_commitment_inclusion_proof {
+            root.ssz_append(bytes);
+        }
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, SszError> {
+        check_length(bytes, Self::SSZ_SIZE)?;
+        let mut fields = Fields(bytes);
+        let index = fields.next()?;
+        let blob = fields.next()?;
+        let kzg_commitment = fields.next()?;
+        let kzg_proof = fields.next()?;
+        let signed_block_header = fields.next()?;
+        let mut kzg_commitment_inclusion_proof = [[0u8; 32]; KZG_COMMITMENT_INCLUSION_PROOF_DEPTH];
+        for root in &mut kzg_commitment_inclusion_proof {
+            *root = fields.next()?;
+        }
+        Ok(BlobSidecar { index, blob, kzg_commitment, kzg_proof, signed_block_header, kzg_commitment_inclusion_proof })
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        merkleize(&[
+            self.index.hash_tree_root(),
+            self.blob.hash_tree_root(),
+            self.kzg_commitment.hash_tree_root(),
+            self.kzg_proof.hash_tree_root(),
+            self.signed_block_header.hash_tree_root(),
+            merkleize(&self.kzg_commitment_inclusion_proof),
+        ])
+    }
+}
+
+impl BlobSidecar {
+    /// Checks that the commitment is in the block body that the header commits to
+    pub fn verify_inclusion_proof(&self) -> bool {
+        verify_commitment_inclusion_proof(
+            &self.kzg_commitment,
+            self.index,
+            &self.kzg_commitment_inclusion_proof,
+            &self.signed_block_header.message.body_root,
+        )
+    }
+
+    /// Checks the inclusion proof of the commitment, and the KZG proof of the blob against the commitment
+    ///
+    /// The signature of the header is not checked.
+    #[cfg(feature = "prover")]
+    pub fn verify(&self, context: &KzgContext) -> Result<bool, SidecarError> {
+        if !self.verify_inclusion_proof() {
+            return Ok(false);
+        }
+        let blob = self.blob.to_polynomial().map_err(SidecarError::Blob)?;
+        let commitment = self.kzg_commitment.to_point().map_err(SidecarError::Commitment)?;
+        let proof = self.kzg_proof.to_point().map_err(SidecarError::Proof)?;
+        Ok(context.verify_blob_kzg_proof(&blob, commitment, proof)?)
+    }
+
+    /// Checks the inclusion proof of the commitment, and the KZG proof of the blob against the commitment,
+    /// with only the opening key
+    ///
+    /// The signature of the header is not checked. The domain of `verifier` must have
+    /// `FIELD_ELEMENTS_PER_BLOB` elements.
+    pub fn verify_with_verifier(&self, verifier: &Verifier) -> Result<bool, SidecarError> {
+        let expected = verifier.domain().size() * SCALAR_SERIALIZED_SIZE;
+        if expected != BYTES_PER_BLOB {
+            return Err(SidecarError::Verifier(VerifierError::BlobSizeMismatch { expected, received: BYTES_PER_BLOB }));
+        }
+        if !self.verify_inclusion_proof() {
+            return Ok(false);
+        }
+        let blob = self.blob.to_polynomial().map_err(SidecarError::Blob)?;
+        let commitment = self.kzg_commitment.to_point().map_err(SidecarError::Commitment)?;
+        let proof = self.kzg_proof.to_point().map_err(SidecarError::Proof)?;
+        Ok(verifier::verify_blob_kzg_proof(verifier.domain(), verifier.opening_key(), &blob, commitment, proof))
+    }
+}
+
+/// The hash tree root of `List[KZGCommitment, MAX_BLOB_COMMITMENTS_PER_BLOCK]`
+pub fn commitments_hash_tree_root(commitments: &[KzgCommitment]) -> Result<Root, SszError> {
+    if commitments.len() > MAX_BLOB_COMMITMENTS_PER_BLOCK {
+        return Err(SszError::TooManyCommitments { num_commitments: commitments.len() });
+    }
+    let leaves: Vec<_> = commitments.iter().map(Ssz::hash_tree_root).collect();
+    let (root, _) = merkle_root_and_branch(&leaves, COMMITMENTS_TREE_DEPTH, 0);
+    Ok(mix_in_length(root, commitments.len()))
+}
+
+/// The root of a block body, given the hash tree roots of its fields in order
+///
+/// Taking the field roots keeps this independent of the fork, which only adds fields after
+/// `blob_kzg_commitments`.
+pub fn block_body_root(body_field_roots: &[Root]) -> Result<Root, SszError> {
+    check_block_body_field_count(body_field_roots)?;
+    Ok(merkle_root_and_branch(body_field_roots, BLOCK_BODY_TREE_DEPTH, 0).0)
+}
+
+/// Builds the inclusion proof of the commitment at `index` in a block body
+///
+/// `body_field_roots` are the hash tree roots of the fields of the block body, where the root at
+/// `BLOB_KZG_COMMITMENTS_INDEX` must be the root of `commitments`.
+pub fn compute_commitment_inclusion_proof(
+    body_field_roots: &[Root],
+    commitments: &[KzgCommitment],
+    index: usize,
+) -> Result<[Root; KZG_COMMITMENT_INCLUSION_PROOF_DEPTH], SszError> {
+    check_block_body_field_count(body_field_roots)?;
+    if index >= commitments.len() {
+        return Err(SszError::InvalidCommitmentIndex { index, num_commitments: commitments.len() });
+    }
+    if commitments_hash_tree_root(commitments)? != body_field_roots[BLOB_KZG_COMMITMENTS_INDEX] {
+        return Err(SszError::CommitmentsRootMismatch);
+    }
+
+    let leaves: Vec<_> = commitments.iter().map(Ssz::hash_tree_root).collect();
+    let (_, commitments_branch) = merkle_root_and_branch(&leaves, COMMITMENTS_TREE_DEPTH, index);
+    let (_, body_branch) = merkle_root_and_branch(body_field_roots, BLOCK_BODY_TREE_DEPTH, BLOB_KZG_COMMITMENTS_INDEX);
+
+    let mut proof = [[0u8; 32]; KZG_COMMITMENT_INCLUSION_PROOF_DEPTH];
+    proof[..COMMITMENTS_TREE_DEPTH].copy_from_slice(&commitments_branch);
+    proof[COMMITMENTS_TREE_DEPTH] = (commitments.len() as u64).hash_tree_root();
+    proof[COMMITMENTS_TREE_DEPTH + 1..].copy_from_slice(&body_branch);
+    Ok(proof)
+}
+
+/// Checks the inclusion proof of the commitment at `index` against the root of a block body
+pub fn verify_commitment_inclusion_proof(
+    commitment: &KzgCommitment,
+    index: u64,
+    proof: &[Root; KZG_COMMITMENT_INCLUSION_PROOF_DEPTH],
+    body_root: &Root,
+) -> bool {
+    if index >= MAX_BLOB_COMMITMENTS_PER_BLOCK as u64 {
+        return false;
+    }
+    // The list's data is the left child of the length mix in
+    let subtree_index = (BLOB_KZG_COMMITMENTS_INDEX << (COMMITMENTS_TREE_DEPTH + 1)) | index as usize;
+    is_valid_merkle_branch(commitment.hash_tree_root(), proof, subtree_index, body_root)
+}
+
+// Returns true, if `branch` is the path of siblings from the leaf at `index` to `root`
+fn is_valid_merkle_branch(leaf: Root, branch: &[Root], index: usize, root: &Root) -> bool {
+    let computed = branch.iter().enumerate().fold(leaf, |node, (depth, sibling)| {
+        if (index >> depth) & 1 == 1 {
+            hash_pair(sibling, &node)
+        } else {
+            hash_pair(&node, sibling)
+        }
+    });
+    computed == *root
+}
+
+// Returns the root of the tree of depth `depth` with `leaves` on the left and zero chunks after them,
+// and the siblings on the path from the leaf at `index` to the root
+fn merkle_root_and_branch(leaves: &[Root], depth: usize, index: usize) -> (Root, Vec<Root>) {
+    debug_assert!(leaves.len() <= 1 << depth);
+
+    // The root of a subtree of zero chunks, for each height
+    let mut zero_hashes = vec![[0u8; 32]];
+    for height in 0..depth {
+        zero_hashes.push(hash_pair(&zero_hashes[height], &zero_hashes[height]));
+    }
+
+    let mut layer = leaves.to_vec();
+    let mut branch = Vec::with_capacity(depth);
+    for (height, zero_hash) in zero_hashes.iter().enumerate().take(depth) {
+        let position = index >> height;
+        branch.push(layer.get(position ^ 1).copied().unwrap_or(*zero_hash));
+
+        layer = layer
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(zero_hash)))
+            .collect();
+    }
+    let root = layer.first().copied().unwrap_or(zero_hashes[depth]);
+    (root, branch)
+}
+
+// The root of the chunks, padded with zero chunks to the next power of two
+fn merkleize(chunks: &[Root]) -> Root {
+    let depth = chunks.len().next_power_of_two().trailing_zeros() as usize;
+    merkle_root_and_branch(chunks, depth, 0).0
+}
+
+// The hash tree root of a byte vector, packed into chunks
+fn hash_tree_root_of_bytes(bytes: &[u8]) -> Root {
+    let chunks: Vec<Root> = bytes
+        .chunks(BYTES_PER_CHUNK)
+        .map(|chunk| {
+            let mut root = [0u8; 32];
+            root[..chunk.len()].copy_from_slice(chunk);
+            root
+        })
+        .collect();
+    merkleize(&chunks)
+}
+
+fn mix_in_length(root: Root, length: usize) -> Root {
+    hash_pair(&root, &(length as u64).hash_tree_root())
+}
+
+fn hash_pair(left: &Root, right: &Root) -> Root {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn check_block_body_field_count(body_field_roots: &[Root]) -> Result<(), SszError> {
+    let num_fields = body_field_roots.len();
+    if num_fields <= BLOB_KZG_COMMITMENTS_INDEX || num_fields > 1 << BLOCK_BODY_TREE_DEPTH {
+        return Err(SszError::InvalidBlockBodyFieldCount { num_fields });
+    }
+    Ok(())
+}
+
+fn check_length(bytes: &[u8], expected: usize) -> Result<(), SszError> {
+    if bytes.len() != expected {
+        return Err(SszError::InvalidLength { expected, received: bytes.len() });
+    }
+    Ok(())
+}
+
+// Reads the fixed size fields of a container one after the other
+struct Fields<'a>(&'a [u8]);
+
+impl Fields<'_> {
+    fn next<T: Ssz>(&mut self) -> Result<T, SszError> {
+        if self.0.len() < T::SSZ_SIZE {
+            return Err(SszError::InvalidLength { expected: T::SSZ_SIZE, received: self.0.len() });
+        }
+        let (field, rest) = self.0.split_at(T::SSZ_SIZE);
+        self.0 = rest;
+        T::from_ssz_bytes(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{domain::Domain, verifier::mainnet_opening_key, FIELD_ELEMENTS_PER_BLOB};
+
+    use super::*;
+
+    fn hex(root: &Root) -> String {
+        root.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    // The roots below were computed with an independent implementation of SSZ merkleization
+    fn body_field_roots(commitments: &[KzgCommitment]) -> Vec<Root> {
+        let mut roots: Vec<Root> = (0..13u8).map(|field| Sha256::digest([field]).into()).collect();
+        roots[BLOB_KZG_COMMITMENTS_INDEX] = commitments_hash_tree_root(commitments).unwrap();
+        roots
+    }
+
+    #[test]
+    fn commitment_inclusion_proofs() {
+        let commitments: Vec<_> = (1..=3u8).map(|byte| KzgCommitment([byte; G1_POINT_SERIALIZED_SIZE])).collect();
+        let body_field_roots = body_field_roots(&commitments);
+        assert_eq!(
+            hex(&body_field_roots[BLOB_KZG_COMMITMENTS_INDEX]),
+            "6026a6167f7a59640f0b12631e634232f226b8db109ea8bf8cec3d794fe9f099"
+        );
+        let body_root = block_body_root(&body_field_roots).unwrap();
+        assert_eq!(hex(&body_root), "c41a768b4857e1c5645e5d43ccbfe2d23ef692288b570c0b627203f9d3489032");
+
+        let header = BeaconBlockHeader { slot: 7, proposer_index: 9, parent_root: [1; 32], state_root: [2; 32], body_root };
+        assert_eq!(hex(&header.hash_tree_root()), "e35d02305ff9ecee53842cbe325ffe4e974233e4db3b656239343a928b1469ab");
+
+        for (index, commitment) in commitments.iter().enumerate() {
+            let proof = compute_commitment_inclusion_proof(&body_field_roots, &commitments, index).unwrap();
+            assert!(verify_commitment_inclusion_proof(commitment, index as u64, &proof, &body_root));
+            assert!(!verify_commitment_inclusion_proof(commitment, index as u64 + 1, &proof, &body_root));
+
+            let mut tampered = proof;
+            tampered[COMMITMENTS_TREE_DEPTH][0] += 1;
+            assert!(!verify_commitment_inclusion_proof(commitment, index as u64, &tampered, &body_root));
+        }
+
+        assert_eq!(
+            compute_commitment_inclusion_proof(&body_field_roots, &commitments, 3),
+            Err(SszError::InvalidCommitmentIndex { index: 3, num_commitments: 3 })
+        );
+        assert_eq!(
+            compute_commitment_inclusion_proof(&body_field_roots, &commitments[..2], 0),
+            Err(SszError::CommitmentsRootMismatch)
+        );
+        assert_eq!(
+            block_body_root(&body_field_roots[..11]),
+            Err(SszError::InvalidBlockBodyFieldCount { num_fields: 11 })
+        );
+    }
+
+    #[test]
+    fn sidecar_ssz_round_trip() {
+        let commitments = vec![KzgCommitment([5; G1_POINT_SERIALIZED_SIZE])];
+        let body_field_roots = body_field_roots(&commitments);
+        let message = BeaconBlockHeader { body_root: block_body_root(&body_field_roots).unwrap(), ..Default::default() };
+        let sidecar = BlobSidecar {
+            index: 0,
+            blob: Blob(Box::new([3; BYTES_PER_BLOB])),
+            kzg_commitment: commitments[0],
+            kzg_proof: KzgProof([4; G1_POINT_SERIALIZED_SIZE]),
+            signed_block_header: SignedBeaconBlockHeader { message, signature: BlsSignature([6; BLS_SIGNATURE_SIZE]) },
+            kzg_commitment_inclusion_proof: compute_commitment_inclusion_proof(&body_field_roots, &commitments, 0)
+                .unwrap(),
+        };
+        assert!(sidecar.verify_inclusion_proof());
+        assert_eq!(
+            sidecar.verify_with_verifier(&Verifier::new(Domain::new(FIELD_ELEMENTS_PER_BLOB), mainnet_opening_key())),
+            Err(SidecarError::Commitment(PointDecodingError::InvalidEncoding))
+        );
+        assert_eq!(
+            sidecar.verify_with_verifier(&Verifier::new(Domain::new(16), mainnet_opening_key())),
+            Err(SidecarError::Verifier(VerifierError::BlobSizeMismatch {
+                expected: 16 * SCALAR_SERIALIZED_SIZE,
+                received: BYTES_PER_BLOB
+            }))
+        );
+
+        let bytes = sidecar.to_ssz_bytes();
+        assert_eq!(bytes.len(), BlobSidecar::SSZ_SIZE);
+        assert_eq!(&bytes[..8], &[0; 8]);
+        assert_eq!(BlobSidecar::from_ssz_bytes(&bytes).unwrap(), sidecar);
+        assert_eq!(
+            BlobSidecar::from_ssz_bytes(&bytes[1..]),
+            Err(SszError::InvalidLength { expected: BlobSidecar::SSZ_SIZE, received: BlobSidecar::SSZ_SIZE - 1 })
+        );
+    }
+
+    #[cfg(feature = "embedded-setup")]
+    #[test]
+    fn mainnet_sidecar_verifies() {
+        use crate::embedded_setup;
+
+        let public_parameters = embedded_setup::public_parameters();
+        let context = KzgContext::from_public_parameters(public_parameters, Domain::new(FIELD_ELEMENTS_PER_BLOB)).unwrap();
+        let polynomial = Polynomial::new((0..FIELD_ELEMENTS_PER_BLOB as u64).map(blstrs::Scalar::from).collect());
+        let commitment = context.commit(&polynomial).unwrap();
+        let proof = context.compute_blob_kzg_proof(&polynomial, commitment).unwrap();
+
+        let commitments = vec![KzgCommitment([5; G1_POINT_SERIALIZED_SIZE]), KzgCommitment::from(commitment)];
+        let body_field_roots = body_field_roots(&commitments);
+        let message = BeaconBlockHeader { body_root: block_body_root(&body_field_roots).unwrap(), ..Default::default() };
+        let mut sidecar = BlobSidecar {
+            index: 1,
+            blob: Blob::from_polynomial(&polynomial),
+            kzg_commitment: commitments[1],
+            kzg_proof: KzgProof::from(proof),
+            signed_block_header: SignedBeaconBlockHeader { message, signature: BlsSignature([0; BLS_SIGNATURE_SIZE]) },
+            kzg_commitment_inclusion_proof: compute_commitment_inclusion_proof(&body_field_roots, &commitments, 1)
+                .unwrap(),
+        };
+        let verifier = Verifier::new(Domain::new(FIELD_ELEMENTS_PER_BLOB), mainnet_opening_key());
+        assert!(sidecar.verify(&context).unwrap());
+        assert!(sidecar.verify_with_verifier(&verifier).unwrap());
+
+        sidecar.blob.0[BYTES_PER_BLOB - 1] ^= 1;
+        assert!(!sidecar.verify(&context).unwrap());
+        assert!(!sidecar.verify_with_verifier(&verifier).unwrap());
+        sidecar.index = 0;
+        assert!(!sidecar.verify(&context).unwrap());
+        assert!(!sidecar.verify_with_verifier(&verifier).unwrap());
+    }
+}
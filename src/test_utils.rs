@@ -0,0 +1,32 @@
+// Fixtures that are shared by the tests of the cell, danksharding, sampling and simulation modules
+
+use ff::Field;
+
+use crate::{
+    commit_key::Commitment, danksharding::DankshardingContext, domain::Domain, params::PublicParameters,
+    polynomial::Polynomial,
+};
+
+// The dimensions of the 2D data in the tests: 4 blobs of 16 field elements, with cells of 4
+pub(crate) const BLOB_SIZE: usize = 16;
+pub(crate) const CELL_SIZE: usize = 4;
+pub(crate) const NUM_ROWS: usize = 4;
+
+// An insecure setup for blobs of `blob_size` field elements
+pub(crate) fn public_parameters(blob_size: usize) -> PublicParameters {
+    PublicParameters::from_secret_insecure(123456789, &Domain::new(blob_size))
+}
+
+pub(crate) fn random_blob(blob_size: usize) -> Polynomial {
+    Polynomial::new((0..blob_size).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect())
+}
+
+// A danksharding context, with a random blob for each row and the commitments to the blobs
+pub(crate) fn danksharding_setup() -> (PublicParameters, DankshardingContext, Vec<Polynomial>, Vec<Commitment>) {
+    let public_parameters = public_parameters(BLOB_SIZE);
+    let context = DankshardingContext::new(&public_parameters, BLOB_SIZE, CELL_SIZE, NUM_ROWS).unwrap();
+
+    let blobs: Vec<_> = (0..NUM_ROWS).map(|_| random_blob(BLOB_SIZE)).collect();
+    let commitments = public_parameters.commit_key.commit_many(&blobs);
+    (public_parameters, context, blobs, commitments)
+}
@@ -1,4 +1,10 @@
+use alloc::vec::Vec;
+#[cfg(feature = "prover")]
+use alloc::vec;
+
 use crate::{domain::Domain, polynomial::Polynomial};
+#[cfg(feature = "prover")]
+use crate::polynomial::SparsePolynomial;
 
 use ff::Field;
 
@@ -7,6 +13,7 @@ use ff::Field;
 /// The state being proved is p(z) = y
 /// Where:
 /// - `z` is the point being passed as input
+#[cfg(feature = "prover")]
 pub fn compute(
     poly: &Polynomial,
     input_point: blstrs::Scalar,
@@ -21,6 +28,7 @@ pub fn compute(
     }
 }
 
+#[cfg(feature = "prover")]
 fn compute_quotient_in_domain(
     poly: &Polynomial,
     index_in_domain: usize,
@@ -55,6 +63,7 @@ fn compute_quotient_in_domain(
     Polynomial::new(quotient_poly)
 }
 
+#[cfg(feature = "prover")]
 fn compute_quotient_eval_within_domain(
     poly: &Polynomial,
     index_in_domain: usize,
@@ -78,22 +87,65 @@ fn compute_quotient_eval_within_domain(
         result += numerator * denominator.invert().unwrap()
     }
 
-    todo!()
+    result
 }
+#[cfg(feature = "prover")]
 fn compute_quotient_outside_domain(
     poly: &Polynomial,
     input_point: blstrs::Scalar,
     output_point: blstrs::Scalar,
     domain: &Domain,
 ) -> Polynomial {
-    // Compute the denominator and store it in the quotient vector, to avoid re-allocation
-    let mut quotient: Vec<_> = domain
+    let inverse_denominators = compute_inverse_denominators(input_point, domain);
+    compute_quotient_outside_domain_with_inverses(poly, output_point, inverse_denominators)
+}
+
+/// Computes `1 / (w^i - z)` for every element `w^i` of the domain, where `z` is `input_point`
+///
+/// These are shared by the barycentric evaluation and the quotient for a point outside of the domain.
+///
+/// Panics, if `input_point` is in the domain
+pub fn compute_inverse_denominators(input_point: blstrs::Scalar, domain: &Domain) -> Vec<blstrs::Scalar> {
+    let mut inverse_denominators: Vec<_> = domain
         .roots()
         .iter()
         .map(|domain_element| *domain_element - input_point)
         .collect();
     // This should not panic, since we assume `input_point` is not in the domain
-    serial_batch_inversion(&mut quotient);
+    serial_batch_inversion(&mut inverse_denominators);
+    inverse_denominators
+}
+
+/// Evaluates `poly` at `input_point`, which is outside of the domain, using the barycentric formula
+///
+/// `inverse_denominators` must come from `compute_inverse_denominators` for the same input point.
+pub fn evaluate_outside_domain_with_inverses(
+    poly: &Polynomial,
+    input_point: blstrs::Scalar,
+    inverse_denominators: &[blstrs::Scalar],
+    domain: &Domain,
+) -> blstrs::Scalar {
+    // p(z) = (z^n - 1) / n * \sum_i f_i * w^i / (z - w^i)
+    //      = -(z^n - 1) / n * \sum_i f_i * w^i / (w^i - z)
+    let mut sum = blstrs::Scalar::zero();
+    for ((evaluation, root), inverse) in poly.evaluations.iter().zip(domain.roots()).zip(inverse_denominators) {
+        sum += evaluation * root * inverse;
+    }
+    let domain_size = domain.size() as u64;
+    -sum * (input_point.pow_vartime([domain_size]) - blstrs::Scalar::one()) * domain.domain_size_inv
+}
+
+/// Computes the quotient polynomial for a kzg proof at a point outside of the domain
+///
+/// `inverse_denominators` must come from `compute_inverse_denominators` for the input point,
+/// they are reused as the storage for the quotient.
+#[cfg(feature = "prover")]
+pub fn compute_quotient_outside_domain_with_inverses(
+    poly: &Polynomial,
+    output_point: blstrs::Scalar,
+    inverse_denominators: Vec<blstrs::Scalar>,
+) -> Polynomial {
+    let mut quotient = inverse_denominators;
 
     // Compute the numerator polynomial and multiply it by the quotient which holds the
     // denominator
@@ -114,8 +166,67 @@ fn compute_quotient_outside_domain(
     Polynomial::new(quotient)
 }
 
+/// Computes the quotient polynomial for a kzg proof of `poly` at the domain element with index `index_in_domain`,
+/// when `poly` is zero at that element
+///
+/// The numerator `f(x) - 0` is zero wherever `poly` is zero, so the quotient is only non-zero
+/// at the non-zero entries of `poly` and at the opening point itself.
+#[cfg(feature = "prover")]
+pub fn compute_sparse_quotient_in_domain(
+    poly: &SparsePolynomial,
+    index_in_domain: usize,
+    domain: &Domain,
+) -> SparsePolynomial {
+    assert!(poly.get(index_in_domain).is_zero_vartime(), "the polynomial must be zero at the opening point");
+
+    let input_point = domain[index_in_domain];
+    let mut denominator: Vec<_> = poly.iter().map(|(index, _)| domain[index] - input_point).collect();
+    serial_batch_inversion(&mut denominator);
 
-use std::ops::MulAssign;
+    let mut quotient = SparsePolynomial::new(poly.size());
+    // q(w^m) = \sum_i f_i * w^i / (w^m * (w^m - w^i)) = -w^(-m) * \sum_i q_i * w^i
+    let mut quotient_at_input_point = blstrs::Scalar::zero();
+    for ((index, evaluation), denominator_i) in poly.iter().zip(denominator) {
+        let quotient_i = evaluation * denominator_i;
+        quotient_at_input_point -= quotient_i * domain[index];
+        quotient.insert(index, quotient_i);
+    }
+    quotient.insert(index_in_domain, quotient_at_input_point * domain.inverse_roots[index_in_domain]);
+
+    quotient
+}
+
+/// Evaluates `poly` at `input_point`, which must be outside of the domain, and computes the
+/// quotient polynomial for a kzg proof of that evaluation
+///
+/// The inverses of `w^i - z` are computed once and shared between the evaluation and the quotient.
+/// The quotient is dense, since the numerator `f(x) - y` is `-y` wherever `poly` is zero.
+#[cfg(feature = "prover")]
+pub fn compute_sparse_quotient_outside_domain(
+    poly: &SparsePolynomial,
+    input_point: blstrs::Scalar,
+    domain: &Domain,
+) -> (blstrs::Scalar, Polynomial) {
+    let inverses = compute_inverse_denominators(input_point, domain);
+
+    // The barycentric formula, written in terms of 1 / (w^i - z)
+    let mut sum = blstrs::Scalar::zero();
+    for (index, evaluation) in poly.iter() {
+        sum += evaluation * domain[index] * inverses[index];
+    }
+    let domain_size = domain.size() as u64;
+    let output_point = -sum * (input_point.pow_vartime([domain_size]) - blstrs::Scalar::one()) * domain.domain_size_inv;
+
+    let mut quotient: Vec<_> = inverses.iter().map(|inverse| -output_point * inverse).collect();
+    for (index, evaluation) in poly.iter() {
+        quotient[index] += evaluation * inverses[index];
+    }
+
+    (output_point, Polynomial::new(quotient))
+}
+
+
+use core::ops::MulAssign;
 
 /// Given a vector of field elements {v_i}, compute the vector {coeff * v_i^(-1)}
 /// This method is explicitly single core.
@@ -151,7 +262,35 @@ pub fn serial_batch_inversion(v: &mut [blstrs::Scalar]) {
     {
         // tmp := tmp * f; f := tmp * s = 1/f
         let new_tmp = tmp * *f;
-        *f = tmp * &s;
+        *f = tmp * s;
         tmp = new_tmp;
     }
 }
+
+/// Reverses the lowest `log2(size)` bits of `index`, where `size` is a power of two
+///
+/// This is the index that `reverse_bit_order` moves the element at `index` to.
+pub fn reverse_bits(index: usize, size: usize) -> usize {
+    if size == 1 {
+        return 0;
+    }
+    index.reverse_bits() >> (usize::BITS - size.trailing_zeros())
+}
+
+/// Permutes `values` so that the element at index `i` moves to the index whose bits are the reverse of `i`
+///
+/// Panics, if the number of values is not a power of two
+pub fn reverse_bit_order<T>(values: &mut [T]) {
+    let n = values.len();
+    assert!(n.is_power_of_two(), "the number of values must be a power of two, got {n}");
+    if n == 1 {
+        return;
+    }
+
+    for i in 0..n {
+        let reversed = reverse_bits(i, n);
+        if i < reversed {
+            values.swap(i, reversed);
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use alloc::vec::Vec;
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    domain::Domain,
+    opening_key::OpeningKey,
+    polynomial::Polynomial,
+    proof::Proof,
+    serialization::{deserialize_blob, deserialize_g1_point, serialize_blob, BlobDecodingError, PointDecodingError},
+    G1_POINT_SERIALIZED_SIZE, OPENING_KEY_SERIALIZED_SIZE, SCALAR_SERIALIZED_SIZE,
+};
+
+// The domain separator for the Fiat-Shamir challenge of a blob proof
+const FIAT_SHAMIR_PROTOCOL_DOMAIN: &[u8] = b"FSBLOBVERIFY_V1_";
+// The domain separator for the Fiat-Shamir challenge that combines a batch of blob proofs
+const RANDOM_CHALLENGE_KZG_BATCH_DOMAIN: &[u8] = b"RCKZGBATCH___V1_";
+
+// The opening key of the mainnet trusted setup, extracted by the build script from `trusted_setup.txt`
+static MAINNET_OPENING_KEY: &[u8; OPENING_KEY_SERIALIZED_SIZE] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/mainnet_opening_key.bin"));
+
+/// Returns the opening key of the mainnet trusted setup
+///
+/// Unlike the `embedded-setup` feature, this only embeds the three points a verifier needs.
+pub fn mainnet_opening_key() -> OpeningKey {
+    OpeningKey::from_bytes(MAINNET_OPENING_KEY).expect("the mainnet opening key is valid")
+}
+
+/// Errors that can occur when the inputs of a `Verifier` cannot be decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifierError {
+    /// A commitment or proof is not a valid compressed G1 point
+    InvalidPoint(PointDecodingError),
+    /// A field element is not smaller than the modulus
+    NonCanonicalFieldElement,
+    /// A blob is not a valid serialized blob
+    InvalidBlob(BlobDecodingError),
+    /// The blob is not `SCALAR_SERIALIZED_SIZE` bytes per element in the domain, the sizes are in bytes
+    BlobSizeMismatch { expected: usize, received: usize },
+    /// There is not one commitment and one proof per blob
+    LengthMismatch { num_blobs: usize, num_commitments: usize, num_proofs: usize },
+}
+
+impl core::fmt::Display for VerifierError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VerifierError::InvalidPoint(error) => write!(f, "{error}"),
+            VerifierError::NonCanonicalFieldElement => write!(f, "field element is not smaller than the modulus"),
+            VerifierError::InvalidBlob(error) => write!(f, "{error}"),
+            VerifierError::BlobSizeMismatch { expected, received } => {
+                write!(f, "blob has {received} bytes, but the domain needs {expected} bytes")
+            }
+            VerifierError::LengthMismatch { num_blobs, num_commitments, num_proofs } => {
+                write!(f, "{num_commitments} commitments and {num_proofs} proofs for {num_blobs} blobs")
+            }
+        }
+    }
+}
+
+impl core::error::Error for VerifierError {}
+
+impl From<PointDecodingError> for VerifierError {
+    fn from(error: PointDecodingError) -> Self {
+        VerifierError::InvalidPoint(error)
+    }
+}
+
+impl From<BlobDecodingError> for VerifierError {
+    fn from(error: BlobDecodingError) -> Self {
+        VerifierError::InvalidBlob(error)
+    }
+}
+
+/// Verifies proofs with only the opening key, for light clients that never commit or prove
+///
+/// The inputs are the serialized commitments, proofs, field elements and blobs, as they arrive
+/// over the network. Unlike `KzgContext`, this does not need the commit key.
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    domain: Domain,
+    opening_key: OpeningKey,
+}
+
+impl Verifier {
+    /// Creates a verifier for blobs with one field element per element in `domain`
+    pub fn new(domain: Domain, opening_key: OpeningKey) -> Verifier {
+        Verifier { domain, opening_key }
+    }
+
+    pub fn domain(&self) -> &Domain {
+        &self.domain
+    }
+
+    pub fn opening_key(&self) -> &OpeningKey {
+        &self.opening_key
+    }
+
+    /// Checks that the polynomial committed to by `commitment` evaluates to `output_point` at `input_point`
+    pub fn verify_kzg_proof(
+        &self,
+        commitment: &[u8; G1_POINT_SERIALIZED_SIZE],
+        input_point: &[u8; SCALAR_SERIALIZED_SIZE],
+        output_point: &[u8; SCALAR_SERIALIZED_SIZE],
+        proof: &[u8; G1_POINT_SERIALIZED_SIZE],
+    ) -> Result<bool, VerifierError> {
+        let proof = Proof {
+            polynomial_commitment: deserialize_g1_point(commitment)?,
+            quotient_commitment: deserialize_g1_point(proof)?,
+            output_point: decode_scalar(output_point)?,
+        };
+        Ok(proof.verify(decode_scalar(input_point)?, &self.opening_key))
+    }
+
+    /// Verifies a proof from `KzgContext::compute_blob_kzg_proof`
+    pub fn verify_blob_kzg_proof(
+        &self,
+        blob: &[u8],
+        commitment: &[u8; G1_POINT_SERIALIZED_SIZE],
+        proof: &[u8; G1_POINT_SERIALIZED_SIZE],
+    ) -> Result<bool, VerifierError> {
+        let blob = self.decode_blob(blob)?;
+        let commitment = deserialize_g1_point(commitment)?;
+        let proof = deserialize_g1_point(proof)?;
+        Ok(verify_blob_kzg_proof(&self.domain, &self.opening_key, &blob, commitment, proof))
+    }
+
+    /// Verifies many proofs from `KzgContext::compute_blob_kzg_proof` at once
+    pub fn verify_blob_kzg_proof_batch(
+        &self,
+        blobs: &[&[u8]],
+        commitments: &[[u8; G1_POINT_SERIALIZED_SIZE]],
+        proofs: &[[u8; G1_POINT_SERIALIZED_SIZE]],
+    ) -> Result<bool, VerifierError> {
+        if commitments.len() != blobs.len() || proofs.len() != blobs.len() {
+            return Err(VerifierError::LengthMismatch {
+                num_blobs: blobs.len(),
+                num_commitments: commitments.len(),
+                num_proofs: proofs.len(),
+            });
+        }
+
+        let blobs = blobs.iter().map(|blob| self.decode_blob(blob)).collect::<Result<Vec<_>, _>>()?;
+        let commitments = commitments.iter().map(deserialize_g1_point).collect::<Result<Vec<_>, _>>()?;
+        let proofs = proofs.iter().map(deserialize_g1_point).collect::<Result<Vec<_>, _>>()?;
+        Ok(verify_blob_kzg_proof_batch(&self.domain, &self.opening_key, &blobs, &commitments, &proofs))
+    }
+
+    fn decode_blob(&self, blob: &[u8]) -> Result<Polynomial, VerifierError> {
+        let expected = self.domain.size() * SCALAR_SERIALIZED_SIZE;
+        if blob.len() != expected {
+            return Err(VerifierError::BlobSizeMismatch { expected, received: blob.len() });
+        }
+        Ok(deserialize_blob(blob)?)
+    }
+}
+
+fn decode_scalar(bytes: &[u8; SCALAR_SERIALIZED_SIZE]) -> Result<blstrs::Scalar, VerifierError> {
+    Option::from(blstrs::Scalar::from_bytes_be(bytes)).ok_or(VerifierError::NonCanonicalFieldElement)
+}
+
+// Verifies a blob proof, the blob must have one evaluation per element in the domain
+pub(crate) fn verify_blob_kzg_proof(
+    domain: &Domain,
+    opening_key: &OpeningKey,
+    blob: &Polynomial,
+    commitment: blstrs::G1Affine,
+    proof: blstrs::G1Affine,
+) -> bool {
+    let challenge = compute_challenge(blob, &commitment);
+    let output_point = blob.evaluate(challenge, domain);
+    let proof = Proof { polynomial_commitment: commitment, quotient_commitment: proof, output_point };
+    proof.verify(challenge, opening_key)
+}
+
+// Verifies a batch of blob proofs, the inputs must have the same length and every blob must have
+// one evaluation per element in the domain
+pub(crate) fn verify_blob_kzg_proof_batch(
+    domain: &Domain,
+    opening_key: &OpeningKey,
+    blobs: &[Polynomial],
+    commitments: &[blstrs::G1Affine],
+    proofs: &[blstrs::G1Affine],
+) -> bool {
+    let input_points: Vec<_> =
+        blobs.iter().zip(commitments).map(|(blob, commitment)| compute_challenge(blob, commitment)).collect();
+    let output_points: Vec<_> =
+        blobs.iter().zip(&input_points).map(|(blob, input_point)| blob.evaluate(*input_point, domain)).collect();
+
+    let mut hasher = Sha256::new();
+    hasher.update(RANDOM_CHALLENGE_KZG_BATCH_DOMAIN);
+    hasher.update((domain.size() as u64).to_be_bytes());
+    hasher.update((blobs.len() as u64).to_be_bytes());
+    for (((commitment, input_point), output_point), proof) in
+        commitments.iter().zip(&input_points).zip(&output_points).zip(proofs)
+    {
+        hasher.update(commitment.to_compressed());
+        hasher.update(input_point.to_bytes_be());
+        hasher.update(output_point.to_bytes_be());
+        hasher.update(proof.to_compressed());
+    }
+    let challenge = hash_to_scalar(hasher);
+
+    opening_key.verify_batch(&input_points, &output_points, commitments, proofs, challenge)
+}
+
+// Hashes the blob and its commitment to the evaluation point of a blob proof
+//
+// This is the hash of the domain separator, the number of field elements as a 16 byte big endian
+// integer, the serialized blob and the compressed commitment, reduced modulo the field order.
+pub(crate) fn compute_challenge(blob: &Polynomial, commitment: &blstrs::G1Affine) -> blstrs::Scalar {
+    let mut hasher = Sha256::new();
+    hasher.update(FIAT_SHAMIR_PROTOCOL_DOMAIN);
+    hasher.update((blob.evaluations.len() as u128).to_be_bytes());
+    hasher.update(serialize_blob(blob));
+    hasher.update(commitment.to_compressed());
+    hash_to_scalar(hasher)
+}
+
+// Reduces the hash, read as a big endian integer, modulo the field order
+fn hash_to_scalar(hasher: Sha256) -> blstrs::Scalar {
+    let hash: [u8; 32] = hasher.finalize().into();
+
+    // The hash as a big endian integer, 64 bits at a time
+    let two_pow_64 = blstrs::Scalar::from(u64::MAX) + blstrs::Scalar::from(1u64);
+    hash.chunks_exact(8).fold(blstrs::Scalar::from(0u64), |acc, limb| {
+        acc * two_pow_64 + blstrs::Scalar::from(u64::from_be_bytes(limb.try_into().unwrap()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "prover")]
+    #[test]
+    fn verify_serialized_proofs() {
+        use ff::Field;
+
+        use crate::{context::KzgContext, params::PublicParameters};
+
+        let public_parameters = PublicParameters::from_secret_insecure(123456789, &Domain::new(16));
+        let context = KzgContext::from_public_parameters(&public_parameters, Domain::new(16)).unwrap();
+        let verifier = Verifier::new(Domain::new(16), public_parameters.opening_key.clone());
+
+        let mut blobs = Vec::new();
+        let mut commitments = Vec::new();
+        let mut proofs = Vec::new();
+        for _ in 0..3 {
+            let blob = Polynomial::new((0..16).map(|_| blstrs::Scalar::random(&mut rand::thread_rng())).collect());
+            let commitment = context.commit(&blob).unwrap();
+            proofs.push(context.compute_blob_kzg_proof(&blob, commitment).unwrap().to_compressed());
+            commitments.push(commitment.to_compressed());
+            blobs.push(serialize_blob(&blob));
+
+            let input_point = blstrs::Scalar::from(1234u64);
+            let proof = context.prove(&blob, commitment, input_point).unwrap();
+            let (input_point, output_point) = (input_point.to_bytes_be(), proof.output_point.to_bytes_be());
+            let quotient_commitment = proof.quotient_commitment.to_compressed();
+            assert_eq!(
+                verifier.verify_kzg_proof(&commitment.to_compressed(), &input_point, &output_point, &quotient_commitment),
+                Ok(true)
+            );
+            assert_eq!(
+                verifier.verify_kzg_proof(&commitment.to_compressed(), &output_point, &output_point, &quotient_commitment),
+                Ok(false)
+            );
+        }
+
+        let blobs: Vec<&[u8]> = blobs.iter().map(Vec::as_slice).collect();
+        assert_eq!(verifier.verify_blob_kzg_proof(blobs[0], &commitments[0], &proofs[0]), Ok(true));
+        assert_eq!(verifier.verify_blob_kzg_proof(blobs[0], &commitments[0], &proofs[1]), Ok(false));
+        assert_eq!(verifier.verify_blob_kzg_proof_batch(&blobs, &commitments, &proofs), Ok(true));
+        proofs.swap(0, 1);
+        assert_eq!(verifier.verify_blob_kzg_proof_batch(&blobs, &commitments, &proofs), Ok(false));
+
+        let mut invalid_point = commitments[0];
+        invalid_point[0] &= 0x7f;
+        assert_eq!(
+            verifier.verify_blob_kzg_proof(blobs[0], &invalid_point, &proofs[0]),
+            Err(VerifierError::InvalidPoint(PointDecodingError::InvalidEncoding))
+        );
+        assert_eq!(
+            verifier.verify_kzg_proof(&commitments[0], &[0xff; 32], &[0; 32], &proofs[0]),
+            Err(VerifierError::NonCanonicalFieldElement)
+        );
+        assert_eq!(
+            verifier.verify_blob_kzg_proof(&blobs[0][..8 * SCALAR_SERIALIZED_SIZE], &commitments[0], &proofs[0]),
+            Err(VerifierError::BlobSizeMismatch { expected: 16 * SCALAR_SERIALIZED_SIZE, received: 8 * SCALAR_SERIALIZED_SIZE })
+        );
+        assert_eq!(
+            verifier.verify_blob_kzg_proof(&blobs[0][..100], &commitments[0], &proofs[0]),
+            Err(VerifierError::BlobSizeMismatch { expected: 16 * SCALAR_SERIALIZED_SIZE, received: 100 })
+        );
+        assert_eq!(
+            verifier.verify_blob_kzg_proof_batch(&blobs, &commitments[..2], &proofs),
+            Err(VerifierError::LengthMismatch { num_blobs: 3, num_commitments: 2, num_proofs: 3 })
+        );
+    }
+
+    #[test]
+    fn opening_key_round_trip() {
+        use group::prime::PrimeCurveAffine;
+
+        use crate::opening_key::OpeningKeyDecodingError;
+
+        let opening_key = mainnet_opening_key();
+        assert_eq!(opening_key.g1_gen, blstrs::G1Affine::generator());
+        assert_eq!(opening_key.g2_gen, blstrs::G2Affine::generator());
+        assert_eq!(&opening_key.to_bytes(), MAINNET_OPENING_KEY);
+
+        let mut bytes = opening_key.to_bytes();
+        bytes[0] &= 0x7f;
+        assert_eq!(OpeningKey::from_bytes(&bytes).unwrap_err(), OpeningKeyDecodingError::InvalidG1Generator);
+        let mut bytes = opening_key.to_bytes();
+        bytes[G1_POINT_SERIALIZED_SIZE] &= 0x7f;
+        assert_eq!(OpeningKey::from_bytes(&bytes).unwrap_err(), OpeningKeyDecodingError::InvalidG2Generator);
+        let mut bytes = opening_key.to_bytes();
+        bytes[OPENING_KEY_SERIALIZED_SIZE - 1] ^= 1;
+        assert_eq!(OpeningKey::from_bytes(&bytes).unwrap_err(), OpeningKeyDecodingError::InvalidTauG2);
+    }
+
+    #[cfg(feature = "embedded-setup")]
+    #[test]
+    fn mainnet_opening_key_matches_embedded_setup() {
+        let opening_key = &crate::embedded_setup::public_parameters().opening_key;
+        assert_eq!(mainnet_opening_key().to_bytes(), opening_key.to_bytes());
+    }
+}